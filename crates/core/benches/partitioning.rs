@@ -0,0 +1,85 @@
+//! Throughput of the spatial index backends at the scale of a map covered in
+//! crops.
+//!
+//! Run with `cargo bench -p flowerpot_core`.
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use flowerpot_core::partitioning::{GridIndex, QbvhIndex, SpatialIndex};
+use glam::Vec2;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// The side length of the benchmarked map, with one entry on every tile.
+const MAP_SIZE: usize = 256;
+
+/// The number of entries that move or get queried in each iteration.
+const BATCH: usize = 1024;
+
+/// The search radius of each query, like a fauna's search radius.
+const RADIUS: f32 = 6.0;
+
+fn tile_index<I: SpatialIndex<usize>>(new: impl Fn() -> I) -> I {
+    let mut index = new();
+    for id in 0..MAP_SIZE * MAP_SIZE {
+        let xy = Vec2::new((id % MAP_SIZE) as f32, (id / MAP_SIZE) as f32);
+        index.insert(id, xy);
+    }
+
+    index.flush();
+    index
+}
+
+fn random_positions(rng: &mut StdRng) -> Vec<Vec2> {
+    let max = (MAP_SIZE - 1) as f32;
+    (0..BATCH)
+        .map(|_| Vec2::new(rng.gen_range(0.0..max), rng.gen_range(0.0..max)))
+        .collect()
+}
+
+fn bench_backend<I: SpatialIndex<usize>>(c: &mut Criterion, name: &str, new: impl Fn() -> I) {
+    let mut group = c.benchmark_group(name);
+    group.bench_function("insert every tile", |b| b.iter(|| tile_index(&new)));
+
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut index = tile_index(&new);
+    group.bench_function("move a batch and flush", |b| {
+        b.iter_batched(
+            || random_positions(&mut rng),
+            |positions| {
+                for (id, position) in positions.into_iter().enumerate() {
+                    index.insert(id * 61, position);
+                }
+
+                index.flush();
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    let index = tile_index(&new);
+    let positions = random_positions(&mut rng);
+    group.bench_function("nearest for a batch", |b| {
+        b.iter(|| {
+            for position in positions.iter() {
+                black_box(index.nearest(*position, RADIUS, |id| id % 7 == 0));
+            }
+        })
+    });
+
+    group.bench_function("nearest 8 for a batch", |b| {
+        b.iter(|| {
+            for position in positions.iter() {
+                black_box(index.nearest_k(*position, RADIUS, 8, |_| true));
+            }
+        })
+    });
+
+    group.finish();
+}
+
+fn bench_backends(c: &mut Criterion) {
+    bench_backend(c, "qbvh", QbvhIndex::new);
+    bench_backend(c, "grid", || GridIndex::new(RADIUS));
+}
+
+criterion_group!(benches, bench_backends);
+criterion_main!(benches);
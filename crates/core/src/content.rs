@@ -0,0 +1,689 @@
+//! Static definitions of all item classes, crop classes, workstation classes,
+//! crafting recipes, and fauna species.
+//!
+//! Native tooling (see the `flowerpot_tools` crate) loads and validates the
+//! same data that the server spawns at runtime. Definitions refer to each
+//! other by their string IDs.
+
+use std::collections::HashSet;
+
+use glam::{IVec2, Vec2};
+
+use crate::{
+    fauna::{Diet, Instincts, Metabolism},
+    map::position_to_tile,
+    pathfinding::PathRules,
+    population::LifeCycle,
+    spawning::Biome,
+};
+
+/// The definition of an item class.
+pub struct ItemDef {
+    /// The unique ID of this item class.
+    pub id: &'static str,
+
+    /// The display name of this item class.
+    pub name: &'static str,
+
+    /// The asset path to this item's model prefab.
+    pub prefab_path: Option<&'static str>,
+
+    /// The color of this item's placeholder model.
+    pub color: Option<[f32; 4]>,
+
+    /// The ID of the crop class that this item plants when used on a tile.
+    pub plantable_crop: Option<&'static str>,
+
+    /// The quality of this item, which improves the yields crafted from it.
+    pub quality: f32,
+
+    /// Whether using this item on a free tile places a pollinator hive.
+    pub places_hive: bool,
+
+    /// Whether using this item on a free tile places a livestock pen.
+    pub places_pen: bool,
+
+    /// The nutrients that using this item on a tile adds to its soil.
+    pub fertilizer: f32,
+}
+
+impl ItemDef {
+    pub const fn new(id: &'static str, name: &'static str) -> Self {
+        Self {
+            id,
+            name,
+            prefab_path: None,
+            color: None,
+            plantable_crop: None,
+            quality: 0.0,
+            places_hive: false,
+            places_pen: false,
+            fertilizer: 0.0,
+        }
+    }
+}
+
+/// The definition of a single growth phase of a crop.
+pub struct CropDef {
+    /// The unique ID of this crop class.
+    pub id: &'static str,
+
+    /// The asset URL of this crop's model prefab.
+    pub prefab_url: &'static str,
+
+    /// The ID of the crop class that this crop grows into.
+    pub next_growth_phase: Option<&'static str>,
+
+    /// The ID of the crop class that this crop spreads onto neighboring tiles.
+    pub seed: Option<&'static str>,
+
+    /// The ID of the item class given to players who harvest this crop.
+    pub harvest_item: Option<&'static str>,
+
+    /// The chance from 0 to 1 that a pest decides to eat this crop.
+    pub pest_attractiveness: f32,
+
+    /// The fullness that a pest gains by eating this crop.
+    pub sustenance: f32,
+
+    /// The ID of the crop class that this crop regresses to when eaten by a
+    /// pest. Eaten crops without a damaged phase are destroyed.
+    pub damaged_phase: Option<&'static str>,
+
+    /// The fullness that a pollinator gains by visiting this crop. Crops with
+    /// nectar are flowering and can be pollinated.
+    pub nectar: f32,
+}
+
+impl CropDef {
+    pub const fn new(id: &'static str, prefab_url: &'static str) -> Self {
+        Self {
+            id,
+            prefab_url,
+            next_growth_phase: None,
+            seed: None,
+            harvest_item: None,
+            pest_attractiveness: 0.0,
+            sustenance: 0.0,
+            damaged_phase: None,
+            nectar: 0.0,
+        }
+    }
+}
+
+/// The definition of a workstation class that recipes may require.
+pub struct WorkstationDef {
+    /// The unique ID of this workstation class.
+    pub id: &'static str,
+
+    /// The display name of this workstation class.
+    pub name: &'static str,
+
+    /// The color of this workstation's placeholder model.
+    pub color: [f32; 4],
+}
+
+/// A weighted outcome in a recipe's yield table.
+pub struct YieldDef {
+    /// The ID of the item class yielded, or `None` to yield nothing.
+    pub item: Option<&'static str>,
+
+    /// The relative likelihood of this outcome.
+    pub weight: f32,
+
+    /// The weight added to this outcome per point of ingredient quality.
+    pub quality_weight: f32,
+
+    /// The inclusive range of the quantity of items yielded.
+    pub min_quantity: u32,
+    pub max_quantity: u32,
+}
+
+impl YieldDef {
+    pub const fn new(item: &'static str) -> Self {
+        Self {
+            item: Some(item),
+            weight: 1.0,
+            quality_weight: 0.0,
+            min_quantity: 1,
+            max_quantity: 1,
+        }
+    }
+}
+
+/// The definition of a crafting recipe.
+///
+/// When crafted, one outcome is rolled from each yield table. The first item
+/// of each outcome is placed into the hand that held the matching ingredient,
+/// then any remaining items fill empty hands and spill onto the ground.
+pub struct RecipeDef {
+    pub primary_ingredient: &'static str,
+    pub secondary_ingredient: Option<&'static str>,
+    pub primary_yield: &'static [YieldDef],
+    pub secondary_yield: &'static [YieldDef],
+
+    /// The ID of the workstation class that crafters must be near.
+    pub workstation: Option<&'static str>,
+
+    /// The time in seconds that crafting takes. Zero for instant crafts.
+    pub duration: f32,
+}
+
+impl RecipeDef {
+    pub const fn new(primary_ingredient: &'static str) -> Self {
+        Self {
+            primary_ingredient,
+            secondary_ingredient: None,
+            primary_yield: &[],
+            secondary_yield: &[],
+            workstation: None,
+            duration: 0.0,
+        }
+    }
+}
+
+/// The definition of a fauna species.
+pub struct SpeciesDef {
+    /// The unique ID of this species.
+    pub id: &'static str,
+
+    /// The asset URL of this species' model prefab. Species without one get a
+    /// placeholder model.
+    pub prefab_url: Option<&'static str>,
+
+    /// The uniform scale of this species' model.
+    pub scale: f32,
+
+    /// The color of this species' placeholder model.
+    pub color: [f32; 4],
+
+    /// The radius of the map area that each individual takes up, which
+    /// searches and raycasts measure to instead of its center.
+    pub footprint: f32,
+
+    pub diet: Diet,
+
+    /// Whether carnivores hunt this species.
+    pub is_prey: bool,
+
+    /// Whether wary fauna flee from this species.
+    pub is_threat: bool,
+
+    pub metabolism: Metabolism,
+    pub instincts: Instincts,
+    pub life_cycle: LifeCycle,
+
+    /// The range that each individual's movement cost is randomly picked from.
+    pub movement_cost: (f32, f32),
+    pub movement_distance: f32,
+
+    /// The radius in which this species looks for food.
+    pub search_radius: f32,
+
+    /// The radius in which an herbivore looks for medium crops to eat.
+    /// Species with a radius of zero leave medium crops alone.
+    pub crop_search_radius: f32,
+
+    /// The radius in which this species notices and flees from threats.
+    /// Species with a wariness of zero never flee.
+    pub wariness: f32,
+
+    /// The rules that this species follows when pathing to food and mates.
+    /// Species without rules move straight toward them, like flying
+    /// pollinators.
+    pub path_rules: Option<PathRules>,
+
+    /// The fullness of newly-spawned adults.
+    pub fullness: f32,
+
+    /// The fullness that a carnivore gains by eating this species.
+    pub sustenance: f32,
+
+    /// The nutrients that this species returns to the soil when it starves
+    /// or dies of old age.
+    pub remains: f32,
+
+    /// The ID of the item class that players feed this species to tame it.
+    /// Species without one can't be tamed.
+    pub taming_food: Option<&'static str>,
+
+    /// The trust that this species needs before it's tamed.
+    pub tame_trust: f32,
+
+    /// The ID of the item class that tamed fauna of this species produce.
+    pub produce: Option<&'static str>,
+
+    /// The time in seconds between each item that a tamed fauna produces.
+    pub produce_interval: f32,
+}
+
+/// A rule for populating the world with a species.
+pub struct SpawnerDef {
+    /// The ID of the species spawned.
+    pub species: &'static str,
+
+    /// The kind of tiles that this spawner populates.
+    pub biome: Biome,
+
+    /// The number of adults spawned per tile in the biome.
+    pub density: f32,
+
+    /// The times of day between which this spawner is active, as fractions
+    /// of a day from midnight.
+    pub hours: (f32, f32),
+
+    /// The time in seconds between checks for whether the species has gone
+    /// locally extinct.
+    pub interval: f32,
+}
+
+/// An item placed on the map when the world is created.
+pub struct WorldItemDef {
+    pub item: &'static str,
+    pub position: [f32; 2],
+}
+
+/// A workstation placed on the map when the world is created.
+pub struct WorldWorkstationDef {
+    pub workstation: &'static str,
+    pub position: [f32; 2],
+}
+
+/// A crop planted on the map when the world is created.
+pub struct WorldCropDef {
+    pub crop: &'static str,
+    pub tile: [i32; 2],
+}
+
+/// A crop scattered across the map when the world is created.
+pub struct WildCropDef {
+    pub crop: &'static str,
+
+    /// The fraction of the map's tiles that this crop is scattered on.
+    pub density: f32,
+}
+
+pub const ITEMS: &[ItemDef] = &[
+    ItemDef {
+        color: Some([0.0, 0.0, 1.0, 1.0]),
+        ..ItemDef::new("blue_item", "Blue Item")
+    },
+    ItemDef {
+        color: Some([0.0, 1.0, 0.0, 1.0]),
+        ..ItemDef::new("green_item", "Green Item")
+    },
+    ItemDef {
+        color: Some([1.0, 1.0, 0.0, 1.0]),
+        ..ItemDef::new("yellow_item", "Yellow Item")
+    },
+    ItemDef {
+        prefab_path: Some("assets/items/maize.glb"),
+        ..ItemDef::new("maize", "Maize")
+    },
+    ItemDef {
+        color: Some([0.9, 0.8, 0.5, 1.0]),
+        ..ItemDef::new("cornmeal", "Cornmeal")
+    },
+    ItemDef {
+        prefab_path: Some("assets/items/maize_seeds.glb"),
+        plantable_crop: Some("maize_stage_1"),
+        ..ItemDef::new("maize_seeds", "Maize Seeds")
+    },
+    ItemDef {
+        prefab_path: Some("assets/items/maize_seeds.glb"),
+        color: Some([0.6, 0.2, 0.8, 1.0]),
+        plantable_crop: Some("maize_stage_1"),
+        quality: 1.0,
+        ..ItemDef::new("mutant_maize_seeds", "Mutant Maize Seeds")
+    },
+    ItemDef {
+        prefab_path: Some("assets/items/tomato_seeds.glb"),
+        plantable_crop: Some("tomatoes_stage_1"),
+        ..ItemDef::new("tomato_seeds", "Tomato Seeds")
+    },
+    ItemDef {
+        color: Some([1.0, 0.8, 0.2, 1.0]),
+        places_hive: true,
+        ..ItemDef::new("beehive", "Beehive")
+    },
+    ItemDef {
+        color: Some([0.6, 0.4, 0.2, 1.0]),
+        places_pen: true,
+        ..ItemDef::new("pen", "Pen")
+    },
+    ItemDef {
+        color: Some([0.3, 0.2, 0.1, 1.0]),
+        fertilizer: 3.0,
+        ..ItemDef::new("manure", "Manure")
+    },
+];
+
+pub const CROPS: &[CropDef] = &[
+    CropDef {
+        next_growth_phase: Some("maize_stage_2"),
+        harvest_item: Some("maize_seeds"),
+        ..CropDef::new("maize_stage_1", "assets/crops/maize1.glb")
+    },
+    CropDef {
+        next_growth_phase: Some("maize_stage_3"),
+        harvest_item: Some("maize_seeds"),
+        pest_attractiveness: 0.2,
+        sustenance: 1.0,
+        ..CropDef::new("maize_stage_2", "assets/crops/maize2.glb")
+    },
+    CropDef {
+        next_growth_phase: Some("maize"),
+        harvest_item: Some("maize_seeds"),
+        pest_attractiveness: 0.4,
+        sustenance: 2.0,
+        damaged_phase: Some("maize_stage_2"),
+        ..CropDef::new("maize_stage_3", "assets/crops/maize3.glb")
+    },
+    CropDef {
+        seed: Some("maize_stage_1"),
+        harvest_item: Some("maize"),
+        pest_attractiveness: 0.6,
+        sustenance: 3.0,
+        damaged_phase: Some("maize_stage_3"),
+        ..CropDef::new("maize", "assets/crops/maize4.glb")
+    },
+    CropDef {
+        seed: Some("iris"),
+        nectar: 1.0,
+        ..CropDef::new("iris", "assets/crops/iris.glb")
+    },
+    CropDef {
+        next_growth_phase: Some("tomatoes_stage_2"),
+        harvest_item: Some("tomato_seeds"),
+        ..CropDef::new("tomatoes_stage_1", "assets/crops/tomatoes1.glb")
+    },
+    CropDef {
+        next_growth_phase: Some("tomatoes_stage_3"),
+        harvest_item: Some("tomato_seeds"),
+        ..CropDef::new("tomatoes_stage_2", "assets/crops/tomatoes2.glb")
+    },
+    CropDef {
+        next_growth_phase: Some("tomatoes_stage_4"),
+        harvest_item: Some("tomato_seeds"),
+        pest_attractiveness: 0.3,
+        sustenance: 1.0,
+        ..CropDef::new("tomatoes_stage_3", "assets/crops/tomatoes3.glb")
+    },
+    CropDef {
+        next_growth_phase: Some("tomatoes"),
+        harvest_item: Some("tomato_seeds"),
+        pest_attractiveness: 0.5,
+        sustenance: 2.0,
+        damaged_phase: Some("tomatoes_stage_3"),
+        ..CropDef::new("tomatoes_stage_4", "assets/crops/tomatoes4.glb")
+    },
+    CropDef {
+        seed: Some("tomatoes_stage_1"),
+        harvest_item: Some("tomato_seeds"),
+        pest_attractiveness: 0.9,
+        sustenance: 4.0,
+        damaged_phase: Some("tomatoes_stage_4"),
+        nectar: 0.5,
+        ..CropDef::new("tomatoes", "assets/crops/tomatoes5.glb")
+    },
+];
+
+pub const WORKSTATIONS: &[WorkstationDef] = &[
+    WorkstationDef {
+        id: "seed_press",
+        name: "Seed Press",
+        color: [0.5, 0.3, 0.1, 1.0],
+    },
+    WorkstationDef {
+        id: "mill",
+        name: "Mill",
+        color: [0.6, 0.6, 0.6, 1.0],
+    },
+];
+
+pub const RECIPES: &[RecipeDef] = &[
+    RecipeDef {
+        secondary_ingredient: Some("yellow_item"),
+        primary_yield: &[YieldDef::new("green_item")],
+        ..RecipeDef::new("blue_item")
+    },
+    RecipeDef {
+        primary_yield: &[
+            YieldDef {
+                weight: 19.0,
+                max_quantity: 4,
+                ..YieldDef::new("maize_seeds")
+            },
+            YieldDef {
+                quality_weight: 4.0,
+                ..YieldDef::new("mutant_maize_seeds")
+            },
+        ],
+        workstation: Some("seed_press"),
+        duration: 3.0,
+        ..RecipeDef::new("maize")
+    },
+    RecipeDef {
+        secondary_ingredient: Some("maize"),
+        primary_yield: &[YieldDef::new("cornmeal")],
+        workstation: Some("mill"),
+        duration: 5.0,
+        ..RecipeDef::new("maize")
+    },
+];
+
+pub const WORLD_ITEMS: &[WorldItemDef] = &[
+    WorldItemDef {
+        item: "blue_item",
+        position: [10.0, 15.0],
+    },
+    WorldItemDef {
+        item: "yellow_item",
+        position: [17.0, 13.0],
+    },
+    WorldItemDef {
+        item: "maize_seeds",
+        position: [10.0, 13.0],
+    },
+    WorldItemDef {
+        item: "tomato_seeds",
+        position: [23.0, 17.0],
+    },
+    WorldItemDef {
+        item: "beehive",
+        position: [14.0, 15.0],
+    },
+    WorldItemDef {
+        item: "pen",
+        position: [17.0, 15.0],
+    },
+];
+
+pub const WORLD_WORKSTATIONS: &[WorldWorkstationDef] = &[
+    WorldWorkstationDef {
+        workstation: "seed_press",
+        position: [12.0, 20.0],
+    },
+    WorldWorkstationDef {
+        workstation: "mill",
+        position: [20.0, 20.0],
+    },
+];
+
+pub const WORLD_CROPS: &[WorldCropDef] = &[WorldCropDef {
+    crop: "maize_stage_1",
+    tile: [0, 0],
+}];
+
+// wildflowers for pollinators to find
+pub const WILD_CROPS: &[WildCropDef] = &[WildCropDef {
+    crop: "iris",
+    density: 8.0 / 1024.0,
+}];
+
+pub const SPECIES: &[SpeciesDef] = &[
+    SpeciesDef {
+        id: "bunny",
+        prefab_url: Some("assets/fauna/rabbit.glb"),
+        scale: 1.0,
+        color: [0.9, 0.9, 0.9, 1.0],
+        footprint: 0.3,
+        diet: Diet::Herbivore,
+        is_prey: true,
+        is_threat: false,
+        metabolism: Metabolism {
+            passive_metabolism: 1.0,
+            hunger_rate: 0.1,
+        },
+        instincts: Instincts {
+            hungry_fullness: 2.0,
+            sated_fullness: 6.0,
+            wander_chance: 0.3,
+        },
+        life_cycle: LifeCycle {
+            maturity_age: 30.0,
+            max_age: 300.0,
+            breeding_fullness: 4.0,
+            breeding_cost: 1.0,
+            breeding_interval: 20.0,
+            mate_radius: 1.0,
+            mate_search_radius: 10.0,
+        },
+        movement_cost: (0.4, 0.6),
+        movement_distance: 0.5,
+        search_radius: 10.0,
+        crop_search_radius: 6.0,
+        wariness: 3.0,
+        path_rules: Some(PathRules {
+            medium_crop_cost: 0.0,
+        }),
+        fullness: 1.0,
+        sustenance: 10.0,
+        remains: 2.0,
+        taming_food: Some("maize"),
+        tame_trust: 3.0,
+        produce: Some("manure"),
+        produce_interval: 30.0,
+    },
+    SpeciesDef {
+        id: "fox",
+        // TODO replace with a fox model
+        prefab_url: Some("assets/fauna/rabbit.glb"),
+        scale: 1.5,
+        color: [0.9, 0.4, 0.1, 1.0],
+        footprint: 0.45,
+        diet: Diet::Carnivore,
+        is_prey: false,
+        is_threat: true,
+        metabolism: Metabolism {
+            passive_metabolism: 1.0,
+            hunger_rate: 0.1,
+        },
+        instincts: Instincts {
+            hungry_fullness: 5.0,
+            sated_fullness: 15.0,
+            wander_chance: 0.5,
+        },
+        life_cycle: LifeCycle {
+            maturity_age: 60.0,
+            max_age: 600.0,
+            breeding_fullness: 12.0,
+            breeding_cost: 4.0,
+            breeding_interval: 90.0,
+            mate_radius: 1.0,
+            mate_search_radius: 16.0,
+        },
+        movement_cost: (0.3, 0.4),
+        movement_distance: 0.7,
+        search_radius: 12.0,
+        crop_search_radius: 0.0,
+        wariness: 0.0,
+        // foxes won't push through medium crops, so prey can hide in them
+        path_rules: Some(PathRules {
+            medium_crop_cost: f32::INFINITY,
+        }),
+        fullness: 10.0,
+        sustenance: 0.0,
+        remains: 4.0,
+        taming_food: None,
+        tame_trust: 0.0,
+        produce: None,
+        produce_interval: 0.0,
+    },
+    // bees don't breed, and are spawned by hives instead
+    SpeciesDef {
+        id: "bee",
+        prefab_url: None,
+        scale: 1.0,
+        color: [1.0, 0.9, 0.0, 1.0],
+        footprint: 0.1,
+        diet: Diet::Pollinator,
+        is_prey: false,
+        is_threat: false,
+        metabolism: Metabolism {
+            passive_metabolism: 1.0,
+            hunger_rate: 0.2,
+        },
+        instincts: Instincts {
+            hungry_fullness: 2.0,
+            sated_fullness: 4.0,
+            wander_chance: 0.6,
+        },
+        life_cycle: LifeCycle {
+            maturity_age: 0.0,
+            max_age: 120.0,
+            breeding_fullness: f32::INFINITY,
+            breeding_cost: 0.0,
+            breeding_interval: 0.0,
+            mate_radius: 0.0,
+            mate_search_radius: 0.0,
+        },
+        movement_cost: (0.2, 0.3),
+        movement_distance: 1.0,
+        search_radius: 8.0,
+        crop_search_radius: 0.0,
+        wariness: 0.0,
+        path_rules: None,
+        fullness: 2.0,
+        sustenance: 0.0,
+        remains: 0.0,
+        taming_food: None,
+        tame_trust: 0.0,
+        produce: None,
+        produce_interval: 0.0,
+    },
+];
+
+pub const SPAWNERS: &[SpawnerDef] = &[
+    SpawnerDef {
+        species: "bunny",
+        biome: Biome::Grassland,
+        density: 5.0 / 1024.0,
+        hours: (0.0, 1.0),
+        interval: 30.0,
+    },
+    // foxes come out from dusk until dawn
+    SpawnerDef {
+        species: "fox",
+        biome: Biome::Any,
+        density: 2.0 / 1024.0,
+        hours: (0.75, 0.25),
+        interval: 60.0,
+    },
+];
+
+/// Looks up the definition of a species by its ID.
+pub fn species(id: &str) -> Option<&'static SpeciesDef> {
+    SPECIES.iter().find(|def| def.id == id)
+}
+
+/// The tiles that crops and workstations are placed on when the world is
+/// created, which wild crops are kept off of.
+pub fn placed_tiles() -> HashSet<IVec2> {
+    let crops = WORLD_CROPS.iter().map(|def| IVec2::from_array(def.tile));
+    let workstations = WORLD_WORKSTATIONS
+        .iter()
+        .map(|def| position_to_tile(Vec2::from_array(def.position)));
+
+    crops.chain(workstations).collect()
+}
@@ -0,0 +1,311 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    Rng,
+};
+
+/// A weighted outcome in a recipe's yield table.
+#[derive(Clone, Debug, PartialEq)]
+pub struct YieldOutcome<Id> {
+    /// The item class yielded, or `None` to yield nothing.
+    pub item: Option<Id>,
+    pub weight: f32,
+    pub quality_weight: f32,
+    pub min_quantity: u32,
+    pub max_quantity: u32,
+}
+
+/// A crafting recipe, identified by `id`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Recipe<Id> {
+    pub id: Id,
+    pub primary_ingredient: Id,
+    pub secondary_ingredient: Option<Id>,
+    pub workstation: Option<Id>,
+    pub duration: f32,
+}
+
+/// The set of all available crafting recipes and their yield tables.
+pub struct RecipeStore<Id> {
+    recipes: HashMap<(Id, Option<Id>), Recipe<Id>>,
+    yield_tables: HashMap<(Id, bool), Vec<YieldOutcome<Id>>>,
+}
+
+impl<Id> Default for RecipeStore<Id> {
+    fn default() -> Self {
+        Self {
+            recipes: HashMap::new(),
+            yield_tables: HashMap::new(),
+        }
+    }
+}
+
+impl<Id: Copy + Eq + Hash> RecipeStore<Id> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a recipe. Returns false and leaves the store unchanged if another
+    /// recipe already uses the same ingredients.
+    pub fn insert(&mut self, recipe: Recipe<Id>) -> bool {
+        let key = (recipe.primary_ingredient, recipe.secondary_ingredient);
+        let swapped = recipe
+            .secondary_ingredient
+            .map(|secondary| (secondary, Some(recipe.primary_ingredient)));
+
+        if self.recipes.contains_key(&key) || swapped.is_some_and(|k| self.recipes.contains_key(&k))
+        {
+            return false;
+        }
+
+        self.recipes.insert(key, recipe);
+        true
+    }
+
+    /// Adds an outcome to the primary or secondary yield table of a recipe.
+    pub fn add_yield(&mut self, recipe: Id, is_secondary: bool, outcome: YieldOutcome<Id>) {
+        self.yield_tables
+            .entry((recipe, is_secondary))
+            .or_default()
+            .push(outcome);
+    }
+
+    /// Finds the recipe that uses the given held items as ingredients.
+    ///
+    /// Also returns whether the right hand holds the primary ingredient.
+    pub fn match_ingredients(
+        &self,
+        left_ingredient: Option<Id>,
+        right_ingredient: Option<Id>,
+    ) -> Option<(&Recipe<Id>, bool)> {
+        let lookup = |primary: Option<Id>, secondary| self.recipes.get(&(primary?, secondary));
+
+        if let Some(recipe) = lookup(left_ingredient, right_ingredient) {
+            Some((recipe, false))
+        } else {
+            lookup(right_ingredient, left_ingredient).map(|recipe| (recipe, true))
+        }
+    }
+
+    /// Rolls a recipe's yield tables for the given total ingredient quality.
+    ///
+    /// Returns the item classes yielded by the primary and secondary tables.
+    pub fn roll_yields(
+        &self,
+        recipe: &Recipe<Id>,
+        quality: f32,
+        rng: &mut impl Rng,
+    ) -> (Vec<Id>, Vec<Id>) {
+        let mut roll = |is_secondary| match self.yield_tables.get(&(recipe.id, is_secondary)) {
+            Some(table) => roll_table(table, quality, rng),
+            None => Vec::new(),
+        };
+
+        (roll(false), roll(true))
+    }
+}
+
+/// Rolls a single yield table.
+pub fn roll_table<Id: Copy>(
+    table: &[YieldOutcome<Id>],
+    quality: f32,
+    rng: &mut impl Rng,
+) -> Vec<Id> {
+    let weights = table
+        .iter()
+        .map(|outcome| (outcome.weight + outcome.quality_weight * quality).max(0.0));
+
+    let Ok(distribution) = WeightedIndex::new(weights) else {
+        return Vec::new();
+    };
+    let outcome = &table[distribution.sample(rng)];
+
+    let Some(item) = outcome.item else {
+        return Vec::new();
+    };
+    let max_quantity = outcome.max_quantity.max(outcome.min_quantity);
+    let quantity = rng.gen_range(outcome.min_quantity..=max_quantity);
+    vec![item; quantity as usize]
+}
+
+/// The items that a player ends up with after a craft.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CraftResult<Id> {
+    pub left_held: Option<Id>,
+    pub right_held: Option<Id>,
+
+    /// Items that didn't fit into the player's hands.
+    pub spilled: Vec<Id>,
+}
+
+/// Distributes rolled yields between a player's hands.
+///
+/// The first item of each table goes into the hand that held the matching
+/// ingredient. Remaining items fill empty hands, then spill.
+pub fn distribute_yields<Id>(
+    primary: Vec<Id>,
+    secondary: Vec<Id>,
+    right_is_primary: bool,
+) -> CraftResult<Id> {
+    let (left, right) = if !right_is_primary {
+        (primary, secondary)
+    } else {
+        (secondary, primary)
+    };
+
+    let mut left = left.into_iter();
+    let mut right = right.into_iter();
+    let first_left = left.next();
+    let first_right = right.next();
+    let mut extras = left.chain(right);
+
+    let left_held = first_left.or_else(|| extras.next());
+    let right_held = first_right.or_else(|| extras.next());
+
+    CraftResult {
+        left_held,
+        right_held,
+        spilled: extras.collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    fn recipe(
+        id: &'static str,
+        primary: &'static str,
+        secondary: Option<&'static str>,
+    ) -> Recipe<&'static str> {
+        Recipe {
+            id,
+            primary_ingredient: primary,
+            secondary_ingredient: secondary,
+            workstation: None,
+            duration: 0.0,
+        }
+    }
+
+    fn outcome(item: &'static str, weight: f32, max_quantity: u32) -> YieldOutcome<&'static str> {
+        YieldOutcome {
+            item: Some(item),
+            weight,
+            quality_weight: 0.0,
+            min_quantity: 1,
+            max_quantity,
+        }
+    }
+
+    #[test]
+    fn ingredients_match_in_either_hand() {
+        let mut store = RecipeStore::new();
+        assert!(store.insert(recipe("r", "blue", Some("yellow"))));
+
+        let (matched, right_is_primary) = store
+            .match_ingredients(Some("blue"), Some("yellow"))
+            .unwrap();
+        assert_eq!(matched.id, "r");
+        assert!(!right_is_primary);
+
+        let (_, right_is_primary) = store
+            .match_ingredients(Some("yellow"), Some("blue"))
+            .unwrap();
+        assert!(right_is_primary);
+
+        assert!(store.match_ingredients(Some("blue"), None).is_none());
+    }
+
+    #[test]
+    fn single_ingredients_match_in_either_hand() {
+        let mut store = RecipeStore::new();
+        assert!(store.insert(recipe("r", "maize", None)));
+        assert!(!store.match_ingredients(Some("maize"), None).unwrap().1);
+        assert!(store.match_ingredients(None, Some("maize")).unwrap().1);
+        assert!(store.match_ingredients(None, None).is_none());
+    }
+
+    #[test]
+    fn duplicate_recipes_are_rejected() {
+        let mut store = RecipeStore::new();
+        assert!(store.insert(recipe("a", "blue", Some("yellow"))));
+        assert!(!store.insert(recipe("b", "yellow", Some("blue"))));
+        assert!(!store.insert(recipe("c", "blue", Some("yellow"))));
+        assert!(store.insert(recipe("d", "blue", None)));
+    }
+
+    #[test]
+    fn yields_are_deterministic_and_in_range() {
+        let mut store = RecipeStore::new();
+        let threshing = recipe("r", "maize", None);
+        store.insert(threshing.clone());
+        store.add_yield("r", false, outcome("seeds", 1.0, 4));
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut other_rng = StdRng::seed_from_u64(7);
+        for _ in 0..100 {
+            let (primary, secondary) = store.roll_yields(&threshing, 0.0, &mut rng);
+            assert!((1..=4).contains(&primary.len()));
+            assert!(primary.iter().all(|item| *item == "seeds"));
+            assert!(secondary.is_empty());
+            assert_eq!(
+                store.roll_yields(&threshing, 0.0, &mut other_rng).0,
+                primary
+            );
+        }
+    }
+
+    #[test]
+    fn quality_shifts_outcome_weights() {
+        let table = [
+            outcome("common", 1.0, 1),
+            YieldOutcome {
+                quality_weight: 1.0,
+                ..outcome("rare", 0.0, 1)
+            },
+        ];
+
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..100 {
+            assert_eq!(roll_table(&table, 0.0, &mut rng), vec!["common"]);
+        }
+
+        let rare = (0..1000)
+            .filter(|_| roll_table(&table, 1000.0, &mut rng) == vec!["rare"])
+            .count();
+        assert!(rare > 900);
+    }
+
+    #[test]
+    fn empty_tables_yield_nothing() {
+        let mut rng = StdRng::seed_from_u64(0);
+        assert!(roll_table::<&str>(&[], 0.0, &mut rng).is_empty());
+
+        let nothing = YieldOutcome {
+            item: None,
+            ..outcome("", 1.0, 1)
+        };
+        assert!(roll_table(&[nothing], 0.0, &mut rng).is_empty());
+    }
+
+    #[test]
+    fn extra_yields_fill_hands_then_spill() {
+        let result = distribute_yields(vec![1, 2, 3], vec![], false);
+        assert_eq!(result.left_held, Some(1));
+        assert_eq!(result.right_held, Some(2));
+        assert_eq!(result.spilled, vec![3]);
+
+        let result = distribute_yields(vec![1], vec![4], true);
+        assert_eq!(result.left_held, Some(4));
+        assert_eq!(result.right_held, Some(1));
+        assert!(result.spilled.is_empty());
+
+        let result = distribute_yields::<u32>(vec![], vec![], false);
+        assert_eq!(result.left_held, None);
+        assert_eq!(result.right_held, None);
+    }
+}
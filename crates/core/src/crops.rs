@@ -0,0 +1,196 @@
+//! Growth rules for cover crops and medium crops.
+
+use rand::Rng;
+
+use crate::map::{for_random_neighbors, OrdinalDirection};
+
+/// The growth properties of a medium crop class.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CropClass<C> {
+    /// The crop class that this crop spreads onto neighboring tiles.
+    pub seed: Option<C>,
+
+    /// The crop class that this crop grows into.
+    pub next_growth_phase: Option<C>,
+
+    /// The crop class that this crop regresses to when eaten by a pest. Eaten
+    /// crops without a damaged phase are destroyed.
+    pub damaged_phase: Option<C>,
+
+    /// The chance from 0 to 1 that a pest decides to eat this crop.
+    pub pest_attractiveness: f32,
+
+    /// The fullness that a pest gains by eating this crop.
+    pub sustenance: f32,
+
+    /// The fullness that a pollinator gains by visiting this crop.
+    pub nectar: f32,
+}
+
+impl<C> CropClass<C> {
+    /// Whether pollinators visit crops of this class.
+    pub fn is_flowering(&self) -> bool {
+        self.nectar > 0.0
+    }
+
+    /// Rolls whether a pest decides to eat a crop of this class.
+    pub fn tempts_pest(&self, rng: &mut impl Rng) -> bool {
+        self.pest_attractiveness > 0.0 && rng.gen::<f32>() < self.pest_attractiveness
+    }
+}
+
+/// The number of seeds that a pollinated crop spreads per growth tick.
+pub const POLLINATED_SEEDS: usize = 2;
+
+/// The number of nearby medium crops that a pest considers before settling
+/// for cover crops instead.
+pub const PEST_CROP_CHOICES: usize = 3;
+
+/// The side length of the grid cells that crops are indexed in.
+pub const CROP_GRID_CELL_SIZE: f32 = 4.0;
+
+/// The changes caused by a single growth tick of a medium crop.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Growth<C, N> {
+    /// The neighboring tiles and the crop classes to plant on them.
+    pub spread: Vec<(N, C)>,
+
+    /// The crop class to replace this crop with.
+    pub next_phase: Option<C>,
+}
+
+/// Picks a random free neighbor of a tile for a crop to spread onto.
+///
+/// `neighbor` looks up the neighbor in a given direction, if any.
+pub fn pick_spread_target<N>(
+    rng: &mut impl Rng,
+    neighbor: impl FnMut(OrdinalDirection) -> Option<N>,
+    mut is_free: impl FnMut(&N) -> bool,
+) -> Option<N> {
+    for_random_neighbors(rng, neighbor, |tile| is_free(&tile).then_some(tile))
+}
+
+/// Grows a medium crop of the given class by one tick.
+///
+/// Pollinated crops spread up to [POLLINATED_SEEDS] seeds instead of one.
+pub fn grow_medium<C: Copy, N: PartialEq>(
+    rng: &mut impl Rng,
+    class: &CropClass<C>,
+    pollinated: bool,
+    mut neighbor: impl FnMut(OrdinalDirection) -> Option<N>,
+    mut is_free: impl FnMut(&N) -> bool,
+) -> Growth<C, N> {
+    let mut spread = Vec::new();
+    if let Some(seed) = class.seed {
+        let seeds = if pollinated { POLLINATED_SEEDS } else { 1 };
+        for _ in 0..seeds {
+            let target = pick_spread_target(rng, &mut neighbor, |tile| {
+                is_free(tile) && !spread.iter().any(|(planted, _)| planted == tile)
+            });
+
+            match target {
+                Some(tile) => spread.push((tile, seed)),
+                None => break,
+            }
+        }
+    }
+
+    Growth {
+        spread,
+        next_phase: class.next_growth_phase,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::IVec2;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn spreading_only_picks_free_tiles() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let free = IVec2::new(1, 0);
+        for _ in 0..20 {
+            let target =
+                pick_spread_target(&mut rng, |dir| Some(dir.offset()), |tile| *tile == free);
+            assert_eq!(target, Some(free));
+        }
+
+        let target = pick_spread_target(&mut rng, |dir| Some(dir.offset()), |_| false);
+        assert_eq!(target, None);
+    }
+
+    fn class(seed: Option<&'static str>, next: Option<&'static str>) -> CropClass<&'static str> {
+        CropClass {
+            seed,
+            next_growth_phase: next,
+            damaged_phase: None,
+            pest_attractiveness: 0.0,
+            sustenance: 0.0,
+            nectar: 0.0,
+        }
+    }
+
+    #[test]
+    fn crops_without_seeds_only_grow() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let class = class(None, Some("stage_2"));
+
+        let growth = grow_medium(&mut rng, &class, false, Some, |_| true);
+        assert_eq!(growth.spread, vec![]);
+        assert_eq!(growth.next_phase, Some("stage_2"));
+    }
+
+    #[test]
+    fn mature_crops_spread_seeds() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let class = class(Some("stage_1"), None);
+
+        let growth = grow_medium(&mut rng, &class, false, Some, |_| true);
+        assert!(matches!(growth.spread[..], [(_, "stage_1")]));
+        assert_eq!(growth.next_phase, None);
+    }
+
+    #[test]
+    fn pollinated_crops_spread_more_seeds() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let class = class(Some("stage_1"), None);
+        for _ in 0..20 {
+            let growth = grow_medium(&mut rng, &class, true, |dir| Some(dir.offset()), |_| true);
+            assert_eq!(growth.spread.len(), POLLINATED_SEEDS);
+            assert_ne!(growth.spread[0].0, growth.spread[1].0);
+        }
+
+        let free = IVec2::new(1, 0);
+        let growth = grow_medium(
+            &mut rng,
+            &class,
+            true,
+            |dir| Some(dir.offset()),
+            |tile| *tile == free,
+        );
+        assert_eq!(growth.spread, vec![(free, "stage_1")]);
+    }
+
+    #[test]
+    fn only_attractive_crops_tempt_pests() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let bland = class(None, None);
+        assert!((0..100).all(|_| !bland.tempts_pest(&mut rng)));
+
+        let tasty = CropClass {
+            pest_attractiveness: 1.0,
+            ..bland
+        };
+        assert!((0..100).all(|_| tasty.tempts_pest(&mut rng)));
+
+        let tempting = CropClass {
+            pest_attractiveness: 0.5,
+            ..bland
+        };
+        let tempted = (0..1000).filter(|_| tempting.tempts_pest(&mut rng)).count();
+        assert!((400..600).contains(&tempted));
+    }
+}
@@ -0,0 +1,535 @@
+//! Decision rules for fauna.
+
+use std::f32::consts::TAU;
+
+use glam::Vec2;
+use rand::Rng;
+
+use crate::movement::movement_heading;
+
+/// The rates at which a fauna gets hungry and regains stamina.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Metabolism {
+    /// The stamina passively regained per second.
+    pub passive_metabolism: f32,
+
+    /// The fullness lost per second.
+    pub hunger_rate: f32,
+}
+
+/// How a fauna moves around the map.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Locomotion {
+    /// The stamina spent on each action.
+    pub movement_cost: f32,
+
+    /// The distance in map coordinates of a single hop.
+    pub movement_distance: f32,
+}
+
+/// What a fauna eats.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Diet {
+    /// Eats cover crops.
+    Herbivore,
+
+    /// Hunts and eats prey fauna.
+    Carnivore,
+
+    /// Feeds on the nectar of flowering crops, pollinating them.
+    Pollinator,
+}
+
+/// The per-species thresholds that drive a fauna's behavior.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Instincts {
+    /// Below this fullness, a fauna goes looking for food.
+    pub hungry_fullness: f32,
+
+    /// At or above this fullness, a fauna stops eating and falls asleep.
+    pub sated_fullness: f32,
+
+    /// The chance that an idle fauna wanders on each decision.
+    pub wander_chance: f32,
+}
+
+/// The changing state of a single fauna.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FaunaState {
+    pub stamina: f32,
+    pub fullness: f32,
+}
+
+/// What a fauna currently knows about its surroundings.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Surroundings {
+    /// The sustenance of the food on the fauna's tile, if any.
+    pub food_here: Option<f32>,
+
+    /// The position of the nearest food that the fauna can see.
+    pub food: Option<Vec2>,
+
+    /// The position of the nearest threat that the fauna can see.
+    pub threat: Option<Vec2>,
+
+    /// The position of the nearest mate, if the fauna is ready to breed.
+    pub mate: Option<Vec2>,
+
+    /// The position of the owner of a tamed fauna, if it has fallen behind.
+    pub owner: Option<Vec2>,
+
+    /// The minimum and maximum corners of the area that the fauna can move in.
+    pub bounds: (Vec2, Vec2),
+}
+
+/// The state of a fauna's behavior state machine.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Behavior {
+    /// Fed and with nothing to do.
+    Idle,
+
+    /// Hopping around in random directions.
+    Wander,
+
+    /// Hopping toward the nearest food.
+    SeekFood,
+
+    /// Eating the food on the current tile.
+    Eat,
+
+    /// Hopping away from the nearest threat.
+    Flee,
+
+    /// Sleeping off a full stomach.
+    Sleep,
+
+    /// Hopping toward the nearest mate.
+    SeekMate,
+
+    /// Hopping after its owner.
+    Follow,
+}
+
+impl Behavior {
+    pub const ALL: [Self; 8] = [
+        Self::Idle,
+        Self::Wander,
+        Self::SeekFood,
+        Self::Eat,
+        Self::Flee,
+        Self::Sleep,
+        Self::SeekMate,
+        Self::Follow,
+    ];
+
+    /// Converts this behavior to an index for storage in a component.
+    pub fn index(self) -> u32 {
+        self as u32
+    }
+
+    /// Converts a stored index back into a behavior.
+    pub fn from_index(index: u32) -> Option<Self> {
+        Self::ALL.get(index as usize).copied()
+    }
+}
+
+/// An action chosen by a fauna.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FaunaAction {
+    /// Wait to regain enough stamina to act.
+    Rest,
+
+    /// Eat the food on the current tile, which should then be removed.
+    Eat,
+
+    /// Hop by the given delta in map coordinates.
+    Hop(Vec2),
+
+    /// Stamina was spent but there was nothing to do.
+    Idle,
+}
+
+impl FaunaState {
+    /// Advances hunger and passive metabolism by `dt` seconds.
+    pub fn metabolize(&mut self, metabolism: &Metabolism, dt: f32) {
+        self.fullness -= metabolism.hunger_rate * dt;
+        self.stamina += metabolism.passive_metabolism * dt;
+    }
+
+    /// Whether this fauna has starved to death.
+    pub fn is_starved(&self) -> bool {
+        self.fullness <= 0.0
+    }
+
+    /// Picks the behavior that follows `current`.
+    pub fn decide(
+        &self,
+        current: Behavior,
+        instincts: &Instincts,
+        surroundings: &Surroundings,
+        rng: &mut impl Rng,
+    ) -> Behavior {
+        use Behavior::*;
+
+        if surroundings.threat.is_some() {
+            return Flee;
+        }
+
+        let hungry = self.fullness < instincts.hungry_fullness;
+        if !hungry && surroundings.owner.is_some() {
+            return Follow;
+        }
+
+        if !hungry && surroundings.mate.is_some() {
+            return SeekMate;
+        }
+
+        if current == Sleep && !hungry {
+            return Sleep;
+        }
+
+        if self.fullness >= instincts.sated_fullness {
+            return Sleep;
+        }
+
+        // once a fauna starts feeding it keeps going until it's sated
+        let feeding = matches!(current, SeekFood | Eat);
+        if feeding || hungry {
+            return if surroundings.food_here.is_some() {
+                Eat
+            } else if surroundings.food.is_some() {
+                SeekFood
+            } else {
+                Wander
+            };
+        }
+
+        if rng.gen_bool(instincts.wander_chance.clamp(0.0, 1.0) as f64) {
+            Wander
+        } else {
+            Idle
+        }
+    }
+
+    /// Decides on and performs the next action of a fauna that isn't
+    /// currently moving.
+    ///
+    /// Every decision costs `movement_cost` stamina, so a fauna decides as
+    /// often as its metabolism allows. Returns the new behavior and the
+    /// action to carry out.
+    pub fn update(
+        &mut self,
+        current: Behavior,
+        locomotion: &Locomotion,
+        instincts: &Instincts,
+        position: Vec2,
+        surroundings: &Surroundings,
+        rng: &mut impl Rng,
+    ) -> (Behavior, FaunaAction) {
+        if self.stamina < locomotion.movement_cost {
+            return (current, FaunaAction::Rest);
+        }
+
+        self.stamina -= locomotion.movement_cost;
+
+        let behavior = self.decide(current, instincts, surroundings, rng);
+        let distance = locomotion.movement_distance;
+        let direction = match behavior {
+            Behavior::Idle | Behavior::Sleep => None,
+            Behavior::Eat => {
+                return match surroundings.food_here {
+                    Some(sustenance) => {
+                        self.fullness += sustenance;
+                        (behavior, FaunaAction::Eat)
+                    }
+                    None => (behavior, FaunaAction::Idle),
+                };
+            }
+            Behavior::SeekFood => surroundings.food.map(|food| food - position),
+            Behavior::SeekMate => surroundings.mate.map(|mate| mate - position),
+            Behavior::Follow => surroundings.owner.map(|owner| owner - position),
+            Behavior::Wander => Some(Vec2::from_angle(rng.gen_range(0.0..TAU))),
+            Behavior::Flee => surroundings.threat.map(|threat| match position - threat {
+                Vec2::ZERO => Vec2::from_angle(rng.gen_range(0.0..TAU)),
+                away => away,
+            }),
+        };
+
+        let delta = direction.map(|direction| match behavior {
+            Behavior::SeekFood | Behavior::SeekMate | Behavior::Follow => {
+                direction.clamp_length_max(distance)
+            }
+            _ => direction.normalize_or_zero() * distance,
+        });
+
+        let action = match delta {
+            Some(delta) => hop_within(position, delta, surroundings.bounds),
+            None => FaunaAction::Idle,
+        };
+
+        (behavior, action)
+    }
+}
+
+/// Helper function to hop by a delta without leaving the given bounds.
+fn hop_within(position: Vec2, delta: Vec2, (min, max): (Vec2, Vec2)) -> FaunaAction {
+    let delta = (position + delta).clamp(min, max) - position;
+    match movement_heading(delta) {
+        Some(_) => FaunaAction::Hop(delta),
+        None => FaunaAction::Idle,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    const LOCOMOTION: Locomotion = Locomotion {
+        movement_cost: 0.5,
+        movement_distance: 0.5,
+    };
+
+    const INSTINCTS: Instincts = Instincts {
+        hungry_fullness: 2.0,
+        sated_fullness: 5.0,
+        wander_chance: 0.0,
+    };
+
+    fn surroundings() -> Surroundings {
+        Surroundings {
+            food_here: None,
+            food: None,
+            threat: None,
+            mate: None,
+            owner: None,
+            bounds: (Vec2::splat(-10.0), Vec2::splat(10.0)),
+        }
+    }
+
+    fn update(
+        state: &mut FaunaState,
+        current: Behavior,
+        surroundings: &Surroundings,
+    ) -> (Behavior, FaunaAction) {
+        let mut rng = StdRng::seed_from_u64(0);
+        state.update(
+            current,
+            &LOCOMOTION,
+            &INSTINCTS,
+            Vec2::ZERO,
+            surroundings,
+            &mut rng,
+        )
+    }
+
+    #[test]
+    fn metabolism_drains_fullness_and_restores_stamina() {
+        let mut state = FaunaState {
+            stamina: 0.0,
+            fullness: 1.0,
+        };
+
+        let metabolism = Metabolism {
+            passive_metabolism: 1.0,
+            hunger_rate: 0.1,
+        };
+
+        state.metabolize(&metabolism, 2.0);
+        assert_eq!(state.stamina, 2.0);
+        assert!((state.fullness - 0.8).abs() < 1e-6);
+        assert!(!state.is_starved());
+
+        state.metabolize(&metabolism, 10.0);
+        assert!(state.is_starved());
+    }
+
+    #[test]
+    fn behaviors_round_trip_through_indices() {
+        for behavior in Behavior::ALL {
+            assert_eq!(Behavior::from_index(behavior.index()), Some(behavior));
+        }
+
+        assert_eq!(Behavior::from_index(Behavior::ALL.len() as u32), None);
+    }
+
+    #[test]
+    fn tired_fauna_rest() {
+        let mut state = FaunaState {
+            stamina: 0.1,
+            fullness: 1.0,
+        };
+
+        let here = Surroundings {
+            food_here: Some(2.0),
+            ..surroundings()
+        };
+
+        let (behavior, action) = update(&mut state, Behavior::Idle, &here);
+        assert_eq!(behavior, Behavior::Idle);
+        assert_eq!(action, FaunaAction::Rest);
+        assert_eq!(state.stamina, 0.1);
+    }
+
+    #[test]
+    fn hungry_fauna_eat_food_on_their_tile() {
+        let mut state = FaunaState {
+            stamina: 1.0,
+            fullness: 1.0,
+        };
+
+        let here = Surroundings {
+            food_here: Some(2.0),
+            food: Some(Vec2::X),
+            ..surroundings()
+        };
+
+        let (behavior, action) = update(&mut state, Behavior::Idle, &here);
+        assert_eq!(behavior, Behavior::Eat);
+        assert_eq!(action, FaunaAction::Eat);
+        assert_eq!(state.fullness, 3.0);
+        assert_eq!(state.stamina, 0.5);
+    }
+
+    #[test]
+    fn hungry_fauna_seek_distant_food() {
+        let mut state = FaunaState {
+            stamina: 1.0,
+            fullness: 1.0,
+        };
+
+        let far = Surroundings {
+            food: Some(Vec2::new(3.0, 0.0)),
+            ..surroundings()
+        };
+
+        let (behavior, action) = update(&mut state, Behavior::Idle, &far);
+        assert_eq!(behavior, Behavior::SeekFood);
+        assert_eq!(action, FaunaAction::Hop(Vec2::new(0.5, 0.0)));
+    }
+
+    #[test]
+    fn feeding_continues_until_sated_then_sleeps() {
+        let mut state = FaunaState {
+            stamina: 10.0,
+            fullness: 3.0,
+        };
+
+        let here = Surroundings {
+            food_here: Some(1.5),
+            ..surroundings()
+        };
+
+        // not hungry enough to start eating
+        let (behavior, _) = update(&mut state, Behavior::Idle, &here);
+        assert_eq!(behavior, Behavior::Idle);
+
+        // but keeps eating once started
+        let (behavior, _) = update(&mut state, Behavior::Eat, &here);
+        assert_eq!(behavior, Behavior::Eat);
+        assert_eq!(state.fullness, 4.5);
+
+        let (behavior, _) = update(&mut state, Behavior::Eat, &here);
+        assert_eq!(behavior, Behavior::Eat);
+
+        let (behavior, action) = update(&mut state, Behavior::Eat, &here);
+        assert_eq!(behavior, Behavior::Sleep);
+        assert_eq!(action, FaunaAction::Idle);
+
+        // sleep until hungry
+        state.fullness = 2.5;
+        let (behavior, _) = update(&mut state, Behavior::Sleep, &here);
+        assert_eq!(behavior, Behavior::Sleep);
+
+        state.fullness = 1.5;
+        let (behavior, _) = update(&mut state, Behavior::Sleep, &here);
+        assert_eq!(behavior, Behavior::Eat);
+    }
+
+    #[test]
+    fn threats_cause_fleeing() {
+        let mut state = FaunaState {
+            stamina: 1.0,
+            fullness: 10.0,
+        };
+
+        let threatened = Surroundings {
+            threat: Some(Vec2::new(1.0, 0.0)),
+            ..surroundings()
+        };
+
+        let (behavior, action) = update(&mut state, Behavior::Sleep, &threatened);
+        assert_eq!(behavior, Behavior::Flee);
+        assert_eq!(action, FaunaAction::Hop(Vec2::new(-0.5, 0.0)));
+    }
+
+    #[test]
+    fn fed_fauna_seek_mates() {
+        let mut state = FaunaState {
+            stamina: 10.0,
+            fullness: 10.0,
+        };
+
+        let courting = Surroundings {
+            food_here: Some(1.0),
+            mate: Some(Vec2::new(0.0, 0.25)),
+            ..surroundings()
+        };
+
+        let (behavior, action) = update(&mut state, Behavior::Sleep, &courting);
+        assert_eq!(behavior, Behavior::SeekMate);
+        assert_eq!(action, FaunaAction::Hop(Vec2::new(0.0, 0.25)));
+
+        state.fullness = 1.0;
+        let (behavior, _) = update(&mut state, Behavior::SeekMate, &courting);
+        assert_eq!(behavior, Behavior::Eat);
+    }
+
+    #[test]
+    fn tamed_fauna_follow_their_owners() {
+        let mut state = FaunaState {
+            stamina: 10.0,
+            fullness: 10.0,
+        };
+
+        let following = Surroundings {
+            mate: Some(Vec2::new(0.0, 1.0)),
+            owner: Some(Vec2::new(3.0, 0.0)),
+            ..surroundings()
+        };
+
+        let (behavior, action) = update(&mut state, Behavior::Sleep, &following);
+        assert_eq!(behavior, Behavior::Follow);
+        assert_eq!(action, FaunaAction::Hop(Vec2::new(0.5, 0.0)));
+
+        state.fullness = 1.0;
+        let (behavior, _) = update(&mut state, Behavior::Follow, &following);
+        assert_eq!(behavior, Behavior::Wander);
+    }
+
+    #[test]
+    fn hops_stay_within_bounds() {
+        let mut state = FaunaState {
+            stamina: 10.0,
+            fullness: 1.0,
+        };
+
+        let cornered = Surroundings {
+            threat: Some(Vec2::new(1.0, 0.0)),
+            bounds: (Vec2::new(0.0, -1.0), Vec2::ONE),
+            ..surroundings()
+        };
+
+        let (_, action) = update(&mut state, Behavior::Idle, &cornered);
+        assert_eq!(action, FaunaAction::Idle);
+
+        let (behavior, action) = update(&mut state, Behavior::Idle, &surroundings());
+        assert_eq!(behavior, Behavior::Wander);
+        let FaunaAction::Hop(delta) = action else {
+            panic!("expected a hop, got {:?}", action);
+        };
+
+        assert!((delta.length() - 0.5).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,214 @@
+//! How fauna choose what to eat, on their own tile and around them.
+
+use rand::Rng;
+
+use crate::{crops::CropClass, fauna::Diet};
+
+/// What a fauna would eat if it ate right now.
+///
+/// Crops are given by `C`, such as their tile or entity, and prey by `P`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Meal<C, P> {
+    /// Grazing on a cover crop.
+    CoverCrop(C),
+
+    /// A pest eating a medium crop, which damages it.
+    MediumCrop(C),
+
+    /// A pollinator visiting a flowering crop, which pollinates it.
+    Flower(C),
+
+    /// A carnivore eating its prey.
+    Prey(P),
+}
+
+/// The food on a fauna's tile.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FoodHere<C, P, K> {
+    /// The cover crop on the tile and the fullness that grazing it gives.
+    pub cover_crop: Option<(C, f32)>,
+
+    /// The medium crop on the tile and its class.
+    pub medium_crop: Option<(C, CropClass<K>)>,
+
+    /// The fauna's nearest prey if it's on the tile, and the fullness that
+    /// eating it gives.
+    pub prey: Option<(P, f32)>,
+}
+
+impl<C, P, K> Default for FoodHere<C, P, K> {
+    fn default() -> Self {
+        Self {
+            cover_crop: None,
+            medium_crop: None,
+            prey: None,
+        }
+    }
+}
+
+/// The food that a fauna has found around it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FoodNearby<T, K> {
+    /// The nearest medium crops that tempt pests, nearest first, and their
+    /// classes.
+    pub medium_crops: Vec<(T, CropClass<K>)>,
+
+    /// The nearest cover crop.
+    pub cover_crop: Option<T>,
+
+    /// The nearest flowering crop.
+    pub flower: Option<T>,
+
+    /// The nearest prey.
+    pub prey: Option<T>,
+}
+
+impl<T, K> Default for FoodNearby<T, K> {
+    fn default() -> Self {
+        Self {
+            medium_crops: Vec::new(),
+            cover_crop: None,
+            flower: None,
+            prey: None,
+        }
+    }
+}
+
+/// Picks what a fauna eats on its tile, along with the fullness that it
+/// would gain.
+///
+/// Pests only go for a medium crop if it tempts them, and otherwise graze on
+/// the cover crop. Pollinators visit flowering crops and carnivores eat prey.
+pub fn pick_meal<C, P, K>(
+    diet: Diet,
+    is_pest: bool,
+    here: FoodHere<C, P, K>,
+    rng: &mut impl Rng,
+) -> Option<(Meal<C, P>, f32)> {
+    match diet {
+        Diet::Herbivore => {
+            let crop = here
+                .medium_crop
+                .filter(|(_, class)| is_pest && class.tempts_pest(rng));
+
+            match crop {
+                Some((crop, class)) => Some((Meal::MediumCrop(crop), class.sustenance)),
+                None => here
+                    .cover_crop
+                    .map(|(crop, sustenance)| (Meal::CoverCrop(crop), sustenance)),
+            }
+        }
+        Diet::Pollinator => here
+            .medium_crop
+            .filter(|(_, class)| class.is_flowering())
+            .map(|(crop, class)| (Meal::Flower(crop), class.nectar)),
+        Diet::Carnivore => here
+            .prey
+            .map(|(prey, sustenance)| (Meal::Prey(prey), sustenance)),
+    }
+}
+
+/// Picks which of the food around a fauna it heads for.
+///
+/// Pests consider each of the nearest medium crops in turn, and settle for
+/// the nearest cover crop if none of them tempt it.
+pub fn pick_food<T, K>(diet: Diet, nearby: FoodNearby<T, K>, rng: &mut impl Rng) -> Option<T> {
+    match diet {
+        Diet::Herbivore => nearby
+            .medium_crops
+            .into_iter()
+            .find(|(_, class)| class.tempts_pest(rng))
+            .map(|(crop, _)| crop)
+            .or(nearby.cover_crop),
+        Diet::Pollinator => nearby.flower,
+        Diet::Carnivore => nearby.prey,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    fn class(pest_attractiveness: f32, nectar: f32) -> CropClass<()> {
+        CropClass {
+            seed: None,
+            next_growth_phase: None,
+            damaged_phase: None,
+            pest_attractiveness,
+            sustenance: 2.0,
+            nectar,
+        }
+    }
+
+    #[test]
+    fn pests_eat_tempting_crops_over_cover_crops() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let here = FoodHere::<u32, u32, ()> {
+            cover_crop: Some((1, 1.0)),
+            medium_crop: Some((2, class(1.0, 0.0))),
+            prey: None,
+        };
+
+        let meal = pick_meal(Diet::Herbivore, true, here, &mut rng);
+        assert_eq!(meal, Some((Meal::MediumCrop(2), 2.0)));
+
+        // fauna that aren't pests always graze
+        let meal = pick_meal(Diet::Herbivore, false, here, &mut rng);
+        assert_eq!(meal, Some((Meal::CoverCrop(1), 1.0)));
+
+        let here = FoodHere {
+            medium_crop: Some((2, class(0.0, 0.0))),
+            ..here
+        };
+
+        let meal = pick_meal(Diet::Herbivore, true, here, &mut rng);
+        assert_eq!(meal, Some((Meal::CoverCrop(1), 1.0)));
+    }
+
+    #[test]
+    fn pollinators_only_visit_flowers() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let here = FoodHere::<u32, u32, ()> {
+            cover_crop: Some((1, 1.0)),
+            medium_crop: Some((2, class(0.0, 0.0))),
+            prey: None,
+        };
+
+        assert_eq!(pick_meal(Diet::Pollinator, false, here, &mut rng), None);
+
+        let here = FoodHere {
+            medium_crop: Some((2, class(0.0, 3.0))),
+            ..here
+        };
+
+        let meal = pick_meal(Diet::Pollinator, false, here, &mut rng);
+        assert_eq!(meal, Some((Meal::Flower(2), 3.0)));
+    }
+
+    #[test]
+    fn pests_settle_for_cover_crops() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let nearby = FoodNearby {
+            medium_crops: vec![(1, class(0.0, 0.0)), (2, class(1.0, 0.0))],
+            cover_crop: Some(3),
+            flower: Some(4),
+            prey: Some(5),
+        };
+
+        let food = pick_food(Diet::Herbivore, nearby.clone(), &mut rng);
+        assert_eq!(food, Some(2));
+        let food = pick_food(Diet::Pollinator, nearby.clone(), &mut rng);
+        assert_eq!(food, Some(4));
+        let food = pick_food(Diet::Carnivore, nearby.clone(), &mut rng);
+        assert_eq!(food, Some(5));
+
+        let nearby = FoodNearby {
+            medium_crops: vec![(1, class(0.0, 0.0))],
+            ..nearby
+        };
+
+        assert_eq!(pick_food(Diet::Herbivore, nearby, &mut rng), Some(3));
+    }
+}
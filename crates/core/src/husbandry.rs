@@ -0,0 +1,92 @@
+//! Rules for taming fauna and keeping them as livestock.
+
+use glam::Vec2;
+
+/// The trust that a fauna gains each time a player feeds it.
+pub const FEEDING_TRUST: f32 = 1.0;
+
+/// The distance that tamed fauna keep from their owners before following.
+pub const FOLLOW_DISTANCE: f32 = 1.5;
+
+/// The radius of the area that a pen keeps livestock in.
+pub const PEN_RADIUS: f32 = 2.0;
+
+/// How far outside of a pen's area that livestock can be to enter it.
+pub const PEN_ENTRY_DISTANCE: f32 = 0.25;
+
+/// The changing trust of a wild fauna toward players.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Taming {
+    pub trust: f32,
+}
+
+impl Taming {
+    /// Feeds a wild fauna and returns whether it now trusts players enough to
+    /// be tamed.
+    pub fn feed(&mut self, tame_trust: f32) -> bool {
+        self.trust += FEEDING_TRUST;
+        self.trust >= tame_trust
+    }
+}
+
+/// The changing state of a tamed fauna that produces items.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Producer {
+    /// The time in seconds until the next item is produced.
+    pub cooldown: f32,
+}
+
+impl Producer {
+    /// Advances a producer by `dt` seconds and returns whether it produces an
+    /// item.
+    pub fn tick(&mut self, interval: f32, dt: f32) -> bool {
+        self.cooldown -= dt;
+        if self.cooldown > 0.0 {
+            return false;
+        }
+
+        self.cooldown = interval;
+        true
+    }
+}
+
+/// Returns where a tamed fauna should follow its owner to, or `None` if it's
+/// already close enough.
+pub fn follow_owner(position: Vec2, owner: Vec2) -> Option<Vec2> {
+    (position.distance(owner) > FOLLOW_DISTANCE).then_some(owner)
+}
+
+/// The minimum and maximum corners of the area inside a pen.
+pub fn pen_bounds(center: Vec2) -> (Vec2, Vec2) {
+    (center - PEN_RADIUS, center + PEN_RADIUS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn feeding_builds_trust_until_tamed() {
+        let mut taming = Taming::default();
+        assert!(!taming.feed(3.0));
+        assert!(!taming.feed(3.0));
+        assert!(taming.feed(3.0));
+        assert_eq!(taming.trust, 3.0);
+    }
+
+    #[test]
+    fn producers_produce_on_an_interval() {
+        let mut producer = Producer { cooldown: 5.0 };
+        assert!(!producer.tick(10.0, 4.0));
+        assert!(producer.tick(10.0, 1.0));
+        assert_eq!(producer.cooldown, 10.0);
+        assert!(!producer.tick(10.0, 9.0));
+    }
+
+    #[test]
+    fn livestock_only_follow_distant_owners() {
+        assert_eq!(follow_owner(Vec2::ZERO, Vec2::ONE), None);
+        let owner = Vec2::new(3.0, 0.0);
+        assert_eq!(follow_owner(Vec2::ZERO, owner), Some(owner));
+    }
+}
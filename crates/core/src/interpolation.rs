@@ -0,0 +1,184 @@
+//! Smoothing of remote entities between the snapshots that clients receive.
+
+use std::collections::VecDeque;
+use std::f32::consts::{PI, TAU};
+
+use glam::Vec3;
+
+/// How far in the past in seconds that clients render remote entities, so that
+/// there's usually a later snapshot to blend toward.
+pub const INTERPOLATION_DELAY: f32 = 0.1;
+
+/// The most snapshots that a buffer keeps. Older snapshots are forgotten.
+pub const MAX_SNAPSHOTS: usize = 32;
+
+/// The transform of a remote entity at one moment.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Snapshot {
+    pub translation: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+impl Snapshot {
+    /// Blends from this snapshot to another, turning yaws the short way
+    /// around.
+    pub fn lerp(&self, other: &Self, t: f32) -> Self {
+        let yaw_delta = (other.yaw - self.yaw + PI).rem_euclid(TAU) - PI;
+        Self {
+            translation: self.translation.lerp(other.translation, t),
+            yaw: (self.yaw + yaw_delta * t).rem_euclid(TAU),
+            pitch: self.pitch + (other.pitch - self.pitch) * t,
+        }
+    }
+}
+
+/// The recent snapshots of a remote entity, in the order that they were
+/// received.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SnapshotBuffer {
+    snapshots: VecDeque<(f32, Snapshot)>,
+}
+
+impl SnapshotBuffer {
+    /// Creates an empty buffer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of snapshots in this buffer.
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    /// Whether this buffer has no snapshots.
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Adds a snapshot received at a time in seconds. Snapshots older than the
+    /// last one are ignored.
+    pub fn push(&mut self, time: f32, snapshot: Snapshot) {
+        if let Some((last_time, last)) = self.snapshots.back_mut() {
+            if time <= *last_time {
+                if time == *last_time {
+                    *last = snapshot;
+                }
+
+                return;
+            }
+
+            // entities that start moving again after standing still start
+            // from where they stood, instead of from long ago
+            if time - *last_time > INTERPOLATION_DELAY {
+                let rest = *last;
+                self.snapshots.push_back((time - INTERPOLATION_DELAY, rest));
+            }
+        }
+
+        self.snapshots.push_back((time, snapshot));
+        while self.snapshots.len() > MAX_SNAPSHOTS {
+            self.snapshots.pop_front();
+        }
+    }
+
+    /// Blends the snapshots around a time in seconds, holding the first or
+    /// last one outside of them. Snapshots before that time aren't needed
+    /// again and are forgotten.
+    ///
+    /// Clients sample at the current time minus [INTERPOLATION_DELAY].
+    pub fn sample(&mut self, time: f32) -> Option<Snapshot> {
+        while self.snapshots.len() > 1 && self.snapshots[1].0 <= time {
+            self.snapshots.pop_front();
+        }
+
+        let (start_time, start) = *self.snapshots.front()?;
+        match self.snapshots.get(1) {
+            Some((end_time, end)) if start_time < time => {
+                let t = (time - start_time) / (end_time - start_time);
+                Some(start.lerp(end, t))
+            }
+            _ => Some(start),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(x: f32) -> Snapshot {
+        Snapshot {
+            translation: Vec3::new(x, 0.0, 0.0),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn samples_blend_between_snapshots() {
+        let mut buffer = SnapshotBuffer::new();
+        assert_eq!(buffer.sample(0.0), None);
+
+        buffer.push(1.0, at(0.0));
+        buffer.push(1.05, at(1.0));
+        buffer.push(1.1, at(3.0));
+
+        assert_eq!(buffer.sample(0.5), Some(at(0.0)));
+        let sample = buffer.sample(1.075).unwrap();
+        assert!((sample.translation.x - 2.0).abs() < 1e-3);
+        assert_eq!(buffer.len(), 2);
+
+        assert_eq!(buffer.sample(2.0), Some(at(3.0)));
+        assert_eq!(buffer.len(), 1);
+    }
+
+    #[test]
+    fn stale_snapshots_are_ignored() {
+        let mut buffer = SnapshotBuffer::new();
+        buffer.push(1.0, at(0.0));
+        buffer.push(1.05, at(1.0));
+        buffer.push(1.01, at(5.0));
+        buffer.push(1.05, at(2.0));
+
+        assert_eq!(buffer.len(), 2);
+        assert_eq!(buffer.sample(1.05), Some(at(2.0)));
+    }
+
+    #[test]
+    fn entities_start_moving_from_rest() {
+        let mut buffer = SnapshotBuffer::new();
+        buffer.push(1.0, at(0.0));
+        buffer.push(5.0, at(1.0));
+
+        let time = 5.0 - INTERPOLATION_DELAY;
+        assert_eq!(buffer.sample(time), Some(at(0.0)));
+        let sample = buffer.sample(time + INTERPOLATION_DELAY / 2.0).unwrap();
+        assert!((sample.translation.x - 0.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn buffers_are_capped() {
+        let mut buffer = SnapshotBuffer::new();
+        for frame in 0..MAX_SNAPSHOTS + 10 {
+            buffer.push(frame as f32 * 0.01, at(frame as f32));
+        }
+
+        assert_eq!(buffer.len(), MAX_SNAPSHOTS);
+    }
+
+    #[test]
+    fn yaws_turn_the_short_way() {
+        let start = Snapshot {
+            yaw: TAU - 0.1,
+            ..Default::default()
+        };
+
+        let end = Snapshot {
+            yaw: 0.1,
+            ..Default::default()
+        };
+
+        let yaw = start.lerp(&end, 0.75).yaw;
+        assert!((yaw - 0.05).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,23 @@
+//! Engine-independent game rules for Flowerpot.
+//!
+//! Everything in this crate builds and tests on the host. The Ambient server
+//! and client modules are thin adapters that move state between the ECS and
+//! the plain state types defined here.
+
+pub mod content;
+pub mod crafting;
+pub mod crops;
+pub mod fauna;
+pub mod foraging;
+pub mod husbandry;
+pub mod interpolation;
+pub mod map;
+pub mod movement;
+pub mod partitioning;
+pub mod pathfinding;
+pub mod player;
+pub mod pollination;
+pub mod population;
+pub mod sim;
+pub mod soil;
+pub mod spawning;
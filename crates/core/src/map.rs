@@ -0,0 +1,260 @@
+use std::collections::HashSet;
+
+use glam::{IVec2, Vec2};
+use rand::{seq::SliceRandom, Rng};
+
+use crate::content::WildCropDef;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OrdinalDirection {
+    West,
+    North,
+    East,
+    South,
+}
+
+impl OrdinalDirection {
+    pub const ALL: [Self; 4] = [Self::West, Self::North, Self::East, Self::South];
+
+    pub fn closest_to_vec2(v: Vec2) -> Self {
+        use OrdinalDirection::*;
+        if v.x.abs() > v.y.abs() {
+            if v.x > 0.0 {
+                East
+            } else {
+                West
+            }
+        } else if v.y > 0.0 {
+            South
+        } else {
+            North
+        }
+    }
+
+    /// The offset in tile coordinates of the neighbor in this direction.
+    pub fn offset(&self) -> IVec2 {
+        use OrdinalDirection::*;
+        match self {
+            West => -IVec2::X,
+            North => -IVec2::Y,
+            East => IVec2::X,
+            South => IVec2::Y,
+        }
+    }
+}
+
+/// Converts a position in map coordinates to the coordinates of its tile.
+pub fn position_to_tile(position: Vec2) -> IVec2 {
+    (position + 0.5).floor().as_ivec2()
+}
+
+/// Calls `cb` on each neighbor of a tile in a random order until it returns
+/// `Some`.
+///
+/// `neighbor` looks up the neighbor in a given direction, if any.
+pub fn for_random_neighbors<N, T>(
+    rng: &mut impl Rng,
+    mut neighbor: impl FnMut(OrdinalDirection) -> Option<N>,
+    mut cb: impl FnMut(N) -> Option<T>,
+) -> Option<T> {
+    let mut directions = OrdinalDirection::ALL;
+    directions.shuffle(rng);
+
+    for dir in directions {
+        if let Some(neighbor) = neighbor(dir) {
+            if let Some(t) = cb(neighbor) {
+                return Some(t);
+            }
+        }
+    }
+
+    None
+}
+
+/// Picks the tiles of a map that each wild crop is scattered on when the world
+/// is created, skipping tiles that are already taken. No two crops share a
+/// tile.
+pub fn scatter_wild_crops(
+    defs: &[WildCropDef],
+    size: IVec2,
+    taken: &HashSet<IVec2>,
+    rng: &mut impl Rng,
+) -> Vec<(IVec2, &'static str)> {
+    let tile_count = (size.x.max(0) * size.y.max(0)) as f32;
+    let mut free: Vec<IVec2> = (0..size.y)
+        .flat_map(|y| (0..size.x).map(move |x| IVec2::new(x, y)))
+        .filter(|xy| !taken.contains(xy))
+        .collect();
+
+    let mut scattered = Vec::new();
+    for def in defs {
+        let count = ((def.density * tile_count).round() as usize).min(free.len());
+        free.partial_shuffle(rng, count);
+        scattered.extend(free.drain(..count).map(|xy| (xy, def.crop)));
+    }
+
+    scattered
+}
+
+/// A rectangular grid of tiles with their origin at (0, 0).
+#[derive(Clone, Debug)]
+pub struct TileMap<T> {
+    size: IVec2,
+    tiles: Vec<T>,
+}
+
+impl<T> TileMap<T> {
+    /// Creates a map of the given size, initializing each tile with `init`.
+    pub fn new(size: IVec2, mut init: impl FnMut(IVec2) -> T) -> Self {
+        let size = size.max(IVec2::ZERO);
+        let mut tiles = Vec::with_capacity((size.x * size.y) as usize);
+        for y in 0..size.y {
+            for x in 0..size.x {
+                tiles.push(init(IVec2::new(x, y)));
+            }
+        }
+
+        Self { size, tiles }
+    }
+
+    pub fn size(&self) -> IVec2 {
+        self.size
+    }
+
+    pub fn contains(&self, xy: IVec2) -> bool {
+        xy.cmpge(IVec2::ZERO).all() && xy.cmplt(self.size).all()
+    }
+
+    pub fn get(&self, xy: IVec2) -> Option<&T> {
+        let index = self.index(xy)?;
+        self.tiles.get(index)
+    }
+
+    pub fn get_mut(&mut self, xy: IVec2) -> Option<&mut T> {
+        let index = self.index(xy)?;
+        self.tiles.get_mut(index)
+    }
+
+    /// Returns the coordinates of the neighbor of a tile in a given direction,
+    /// if that neighbor is on the map.
+    pub fn neighbor(&self, xy: IVec2, dir: OrdinalDirection) -> Option<IVec2> {
+        let neighbor = xy + dir.offset();
+        self.contains(neighbor).then_some(neighbor)
+    }
+
+    /// Iterates over the coordinates and contents of every tile.
+    pub fn iter(&self) -> impl Iterator<Item = (IVec2, &T)> {
+        let width = self.size.x;
+        self.tiles.iter().enumerate().map(move |(index, tile)| {
+            let index = index as i32;
+            (IVec2::new(index % width, index / width), tile)
+        })
+    }
+
+    fn index(&self, xy: IVec2) -> Option<usize> {
+        self.contains(xy)
+            .then(|| (xy.y * self.size.x + xy.x) as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn closest_direction() {
+        use OrdinalDirection::*;
+        assert_eq!(OrdinalDirection::closest_to_vec2(Vec2::new(2.0, 1.0)), East);
+        assert_eq!(
+            OrdinalDirection::closest_to_vec2(Vec2::new(-2.0, 1.0)),
+            West
+        );
+        assert_eq!(
+            OrdinalDirection::closest_to_vec2(Vec2::new(0.5, 1.0)),
+            South
+        );
+        assert_eq!(
+            OrdinalDirection::closest_to_vec2(Vec2::new(0.5, -1.0)),
+            North
+        );
+    }
+
+    #[test]
+    fn positions_round_to_nearest_tile() {
+        assert_eq!(position_to_tile(Vec2::new(0.4, 0.6)), IVec2::new(0, 1));
+        assert_eq!(position_to_tile(Vec2::new(-0.6, 2.49)), IVec2::new(-1, 2));
+    }
+
+    #[test]
+    fn tile_map_bounds_and_neighbors() {
+        let map = TileMap::new(IVec2::new(3, 2), |xy| xy);
+        assert_eq!(map.get(IVec2::new(2, 1)), Some(&IVec2::new(2, 1)));
+        assert_eq!(map.get(IVec2::new(3, 0)), None);
+        assert_eq!(map.get(IVec2::new(0, -1)), None);
+
+        let corner = IVec2::ZERO;
+        assert_eq!(map.neighbor(corner, OrdinalDirection::West), None);
+        assert_eq!(map.neighbor(corner, OrdinalDirection::North), None);
+        assert_eq!(
+            map.neighbor(corner, OrdinalDirection::East),
+            Some(IVec2::new(1, 0))
+        );
+
+        assert!(map.iter().all(|(xy, tile)| xy == *tile));
+        assert_eq!(map.iter().count(), 6);
+    }
+
+    #[test]
+    fn wild_crops_are_scattered_on_free_tiles() {
+        let defs = [
+            WildCropDef {
+                crop: "a",
+                density: 0.25,
+            },
+            WildCropDef {
+                crop: "b",
+                density: 0.5,
+            },
+        ];
+
+        let taken = HashSet::from([IVec2::ZERO, IVec2::new(1, 1)]);
+        let mut rng = StdRng::seed_from_u64(0);
+        let scattered = scatter_wild_crops(&defs, IVec2::new(4, 4), &taken, &mut rng);
+
+        let count = |id| scattered.iter().filter(|(_, crop)| *crop == id).count();
+        assert_eq!((count("a"), count("b")), (4, 8));
+
+        let tiles: HashSet<_> = scattered.iter().map(|(xy, _)| *xy).collect();
+        assert_eq!(tiles.len(), scattered.len());
+        assert!(tiles.is_disjoint(&taken));
+        assert!(tiles
+            .iter()
+            .all(|xy| xy.cmpge(IVec2::ZERO).all() && xy.cmplt(IVec2::splat(4)).all()));
+
+        // crops run out of room instead of sharing tiles
+        let crowded = [WildCropDef {
+            crop: "a",
+            density: 2.0,
+        }];
+        let scattered = scatter_wild_crops(&crowded, IVec2::new(4, 4), &taken, &mut rng);
+        assert_eq!(scattered.len(), 14);
+    }
+
+    #[test]
+    fn random_neighbors_visit_each_direction_once() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut visited = Vec::new();
+        let result: Option<()> = for_random_neighbors(&mut rng, Some, |dir| {
+            visited.push(dir);
+            None
+        });
+
+        assert_eq!(result, None);
+        assert_eq!(visited.len(), 4);
+        assert!(OrdinalDirection::ALL
+            .iter()
+            .all(|dir| visited.contains(dir)));
+    }
+}
@@ -0,0 +1,64 @@
+//! Interpolation of entities hopping between two map positions.
+
+use glam::Vec2;
+
+/// Returns how far through a movement lasting `duration` seconds a `step` is,
+/// from zero to one.
+pub fn movement_progress(step: f32, duration: f32) -> f32 {
+    if duration > 0.0 {
+        (step / duration).clamp(0.0, 1.0)
+    } else {
+        1.0
+    }
+}
+
+/// Returns the map position of a movement at the given progress.
+pub fn movement_position(start: Vec2, target: Vec2, progress: f32) -> Vec2 {
+    start.lerp(target, progress)
+}
+
+/// Returns the elevation of a hop with the given peak height at the given
+/// progress.
+pub fn movement_elevation(height: f32, progress: f32) -> f32 {
+    progress * (1.0 - progress) * 4.0 * height
+}
+
+/// Returns the Z rotation that faces along a movement delta, or `None` if the
+/// delta has no direction.
+pub fn movement_heading(delta: Vec2) -> Option<f32> {
+    let theta = -delta.angle_between(-Vec2::X);
+    theta.is_finite().then_some(theta)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress_is_clamped() {
+        assert_eq!(movement_progress(0.5, 0.25), 1.0);
+        assert_eq!(movement_progress(-1.0, 0.25), 0.0);
+        assert_eq!(movement_progress(0.1, 0.0), 1.0);
+        assert!((movement_progress(0.125, 0.25) - 0.5).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn hops_peak_halfway() {
+        assert_eq!(movement_elevation(0.5, 0.0), 0.0);
+        assert_eq!(movement_elevation(0.5, 0.5), 0.5);
+        assert_eq!(movement_elevation(0.5, 1.0), 0.0);
+    }
+
+    #[test]
+    fn positions_interpolate() {
+        let start = Vec2::new(1.0, 1.0);
+        let target = Vec2::new(3.0, 1.0);
+        assert_eq!(movement_position(start, target, 0.5), Vec2::new(2.0, 1.0));
+    }
+
+    #[test]
+    fn zero_delta_has_no_heading() {
+        assert_eq!(movement_heading(Vec2::ZERO), None);
+        assert!(movement_heading(Vec2::X).is_some());
+    }
+}
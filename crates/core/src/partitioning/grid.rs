@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use glam::{IVec2, Vec2};
+
+use super::{Footprint, SpatialIndex};
+
+/// A uniform grid of entries, hashed by the cell that their position is in.
+///
+/// Inserting, moving, and removing entries only touches their cells, so grids
+/// never need to be rebuilt and are always flushed. Queries look through extra
+/// cells to reach the largest footprint that the grid has held since it was
+/// last empty, so grids suit entries with small footprints.
+pub struct GridIndex<T> {
+    cell_size: f32,
+    cells: HashMap<IVec2, Vec<(T, Vec2, Footprint)>>,
+    ids_to_cells: HashMap<T, IVec2>,
+    reach: Vec2,
+}
+
+impl<T> GridIndex<T> {
+    /// Creates an empty grid with square cells of the given side length.
+    ///
+    /// Cells about as large as a typical search radius work best.
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+            ids_to_cells: HashMap::new(),
+            reach: Vec2::ZERO,
+        }
+    }
+
+    /// Helper function to get the cell that a position is in.
+    fn cell(&self, position: Vec2) -> IVec2 {
+        (position / self.cell_size).floor().as_ivec2()
+    }
+}
+
+impl<T: Copy + Eq + Hash> GridIndex<T> {
+    /// Helper function to remove an entry from a cell's list of entries.
+    fn remove_from_cell(&mut self, id: T, cell: IVec2) {
+        let Some(entries) = self.cells.get_mut(&cell) else {
+            return;
+        };
+
+        entries.retain(|(entry, ..)| *entry != id);
+        if entries.is_empty() {
+            self.cells.remove(&cell);
+        }
+    }
+}
+
+impl<T: Copy + Eq + Hash> SpatialIndex<T> for GridIndex<T> {
+    fn len(&self) -> usize {
+        self.ids_to_cells.len()
+    }
+
+    fn is_dirty(&self) -> bool {
+        false
+    }
+
+    fn insert_with_footprint(&mut self, id: T, position: Vec2, footprint: Footprint) {
+        self.reach = self.reach.max(footprint.half_extents());
+
+        let entry = (id, position, footprint);
+        let cell = self.cell(position);
+        if let Some(old_cell) = self.ids_to_cells.insert(id, cell) {
+            if old_cell == cell {
+                let entries = self.cells.entry(cell).or_default();
+                if let Some(old_entry) = entries.iter_mut().find(|(old, ..)| *old == id) {
+                    *old_entry = entry;
+                    return;
+                }
+            } else {
+                self.remove_from_cell(id, old_cell);
+            }
+        }
+
+        self.cells.entry(cell).or_default().push(entry);
+    }
+
+    fn remove(&mut self, id: T) -> bool {
+        let Some(cell) = self.ids_to_cells.remove(&id) else {
+            return false;
+        };
+
+        self.remove_from_cell(id, cell);
+        if self.ids_to_cells.is_empty() {
+            self.reach = Vec2::ZERO;
+        }
+
+        true
+    }
+
+    fn flush(&mut self) {}
+
+    fn candidates(&self, min: Vec2, max: Vec2) -> impl Iterator<Item = (T, Vec2, Footprint)> + '_ {
+        let min = self.cell(min - self.reach);
+        let max = self.cell(max + self.reach);
+        (min.y..=max.y)
+            .flat_map(move |y| (min.x..=max.x).map(move |x| IVec2::new(x, y)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+    }
+}
@@ -0,0 +1,529 @@
+//! Spatial indexing for finding the entries nearest to a point.
+//!
+//! Every index implements [SpatialIndex], which answers queries the same way
+//! no matter which backend stores the entries. [QbvhIndex] suits entries that
+//! move freely or have large footprints, while [GridIndex] is cheaper to
+//! update for dense entries on tile coordinates.
+
+use std::hash::Hash;
+
+use glam::Vec2;
+
+mod grid;
+mod qbvh;
+
+pub use grid::GridIndex;
+pub use qbvh::QbvhIndex;
+
+/// An entry found by a spatial query, along with its distance from the query
+/// to the nearest part of its footprint.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hit<T> {
+    pub id: T,
+    pub distance: f32,
+}
+
+/// The area that an entry covers around its position.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Footprint {
+    /// Just the entry's position.
+    #[default]
+    Point,
+
+    /// A circle with the given radius.
+    Circle(f32),
+
+    /// A box with the given half extents.
+    Box(Vec2),
+}
+
+impl Footprint {
+    /// The half extents of the smallest box around this footprint.
+    pub fn half_extents(&self) -> Vec2 {
+        match *self {
+            Footprint::Point => Vec2::ZERO,
+            Footprint::Circle(radius) => Vec2::splat(radius),
+            Footprint::Box(half_extents) => half_extents,
+        }
+    }
+
+    /// The distance from a point to the nearest part of this footprint when
+    /// it's centered on a position, or 0 if the point is inside of it.
+    pub fn distance(&self, center: Vec2, point: Vec2) -> f32 {
+        match *self {
+            Footprint::Point => center.distance(point),
+            Footprint::Circle(radius) => (center.distance(point) - radius).max(0.0),
+            Footprint::Box(half_extents) => ((point - center).abs() - half_extents)
+                .max(Vec2::ZERO)
+                .length(),
+        }
+    }
+
+    /// Whether this footprint overlaps a box, given by its minimum and
+    /// maximum corners, when it's centered on a position.
+    pub fn overlaps(&self, center: Vec2, min: Vec2, max: Vec2) -> bool {
+        self.distance(center, center.clamp(min, max)) == 0.0
+    }
+
+    /// The distance along a ray to where it enters this footprint when it's
+    /// centered on a position, if it does.
+    ///
+    /// Rays have a radius that grows the footprint by that much, except that
+    /// the corners of boxes stay square.
+    pub fn ray_distance(
+        &self,
+        center: Vec2,
+        origin: Vec2,
+        direction: Vec2,
+        ray_radius: f32,
+    ) -> Option<f32> {
+        match *self {
+            Footprint::Point => ray_circle_distance(origin, direction, center, ray_radius),
+            Footprint::Circle(radius) => {
+                ray_circle_distance(origin, direction, center, radius + ray_radius)
+            }
+            Footprint::Box(half_extents) => {
+                let half_extents = half_extents + ray_radius;
+                ray_box_distance(
+                    origin,
+                    direction,
+                    center - half_extents,
+                    center + half_extents,
+                )
+            }
+        }
+    }
+}
+
+/// A collection of entries, each identified by a unique ID and covering a
+/// footprint around its position, that can be searched by position.
+///
+/// Queries find entries whose footprints overlap them, and measure distances
+/// to the nearest part of each footprint. Entries that are the same distance
+/// away may be found in any order.
+///
+/// Changes made by [SpatialIndex::insert] and [SpatialIndex::remove] are seen
+/// by queries right away, in every backend. Backends may put off restructuring
+/// until the next [SpatialIndex::flush], so that a whole batch of changes is
+/// applied at once and queries stay fast.
+pub trait SpatialIndex<T: Copy + Eq + Hash> {
+    /// The number of entries in this index.
+    fn len(&self) -> usize;
+
+    /// Whether this index has no entries.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether this index has changes that it hasn't restructured itself for
+    /// yet.
+    fn is_dirty(&self) -> bool;
+
+    /// Inserts an entry with a footprint, or moves and resizes it if it's
+    /// already in this index.
+    fn insert_with_footprint(&mut self, id: T, position: Vec2, footprint: Footprint);
+
+    /// Inserts an entry that's just a point, or moves it if it's already in
+    /// this index.
+    fn insert(&mut self, id: T, position: Vec2) {
+        self.insert_with_footprint(id, position, Footprint::Point);
+    }
+
+    /// Removes an entry, returning whether it was in this index.
+    fn remove(&mut self, id: T) -> bool;
+
+    /// Restructures this index for every insertion and removal since the last
+    /// flush, if there were any. Queries give the same results either way.
+    fn flush(&mut self);
+
+    /// Lists at least every entry whose footprint overlaps a box, given by its
+    /// minimum and maximum corners, along with their positions and
+    /// footprints. Entries outside of the box may be listed too.
+    fn candidates(&self, min: Vec2, max: Vec2) -> impl Iterator<Item = (T, Vec2, Footprint)> + '_;
+
+    /// Finds every entry that passes a filter within a radius of a position,
+    /// nearest first.
+    fn within_radius(
+        &self,
+        position: Vec2,
+        radius: f32,
+        filter: impl FnMut(T) -> bool,
+    ) -> Vec<Hit<T>> {
+        let mut hits: Vec<Hit<T>> = hits(self, position, radius, filter).collect();
+        hits.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        hits
+    }
+
+    /// Finds up to `k` entries that pass a filter within a radius of a
+    /// position, nearest first.
+    fn nearest_k(
+        &self,
+        position: Vec2,
+        radius: f32,
+        k: usize,
+        filter: impl FnMut(T) -> bool,
+    ) -> Vec<Hit<T>> {
+        match k {
+            0 => return Vec::new(),
+            1 => return self.nearest(position, radius, filter).into_iter().collect(),
+            _ => {}
+        }
+
+        let mut hits = self.within_radius(position, radius, filter);
+        hits.truncate(k);
+        hits
+    }
+
+    /// Finds the nearest entry that passes a filter within a radius of a
+    /// position.
+    fn nearest(
+        &self,
+        position: Vec2,
+        radius: f32,
+        filter: impl FnMut(T) -> bool,
+    ) -> Option<Hit<T>> {
+        hits(self, position, radius, filter).min_by(|a, b| a.distance.total_cmp(&b.distance))
+    }
+
+    /// Finds every entry that passes a filter and overlaps a box, given by its
+    /// minimum and maximum corners.
+    fn within_aabb(&self, min: Vec2, max: Vec2, mut filter: impl FnMut(T) -> bool) -> Vec<T> {
+        self.candidates(min, max)
+            .filter(|(id, center, footprint)| footprint.overlaps(*center, min, max) && filter(*id))
+            .map(|(id, ..)| id)
+            .collect()
+    }
+
+    /// Finds the first entry that passes a filter along a ray with a radius.
+    ///
+    /// `direction` is normalized, and hits are measured by their distance
+    /// along the ray. See [Footprint::ray_distance] for how rays hit entries.
+    /// Rays that start inside of an entry hit it immediately.
+    fn raycast(
+        &self,
+        origin: Vec2,
+        direction: Vec2,
+        max_distance: f32,
+        radius: f32,
+        mut filter: impl FnMut(T) -> bool,
+    ) -> Option<Hit<T>> {
+        let end = origin + direction * max_distance;
+        self.candidates(origin.min(end) - radius, origin.max(end) + radius)
+            .filter_map(|(id, center, footprint)| {
+                let distance = footprint.ray_distance(center, origin, direction, radius)?;
+                (distance <= max_distance && filter(id)).then_some(Hit { id, distance })
+            })
+            .min_by(|a, b| a.distance.total_cmp(&b.distance))
+    }
+}
+
+/// Helper function to list the entries of an index that pass a filter within
+/// a radius of a position, in no particular order.
+fn hits<'a, T: Copy + Eq + Hash, I: SpatialIndex<T> + ?Sized>(
+    index: &'a I,
+    position: Vec2,
+    radius: f32,
+    mut filter: impl FnMut(T) -> bool + 'a,
+) -> impl Iterator<Item = Hit<T>> + 'a {
+    index
+        .candidates(position - radius, position + radius)
+        .map(move |(id, center, footprint)| Hit {
+            id,
+            distance: footprint.distance(center, position),
+        })
+        .filter(move |hit| hit.distance < radius && filter(hit.id))
+}
+
+/// Helper function to get the distance along a ray to where it enters a
+/// circle, if it does.
+fn ray_circle_distance(origin: Vec2, direction: Vec2, center: Vec2, radius: f32) -> Option<f32> {
+    let offset = origin - center;
+    let c = offset.length_squared() - radius * radius;
+    if c <= 0.0 {
+        return Some(0.0);
+    }
+
+    let b = offset.dot(direction);
+    let discriminant = b * b - c;
+    if b > 0.0 || discriminant < 0.0 {
+        return None;
+    }
+
+    Some(-b - discriminant.sqrt())
+}
+
+/// Helper function to get the distance along a ray to where it enters a box,
+/// given by its minimum and maximum corners, if it does.
+fn ray_box_distance(origin: Vec2, direction: Vec2, min: Vec2, max: Vec2) -> Option<f32> {
+    if origin.cmpge(min).all() && origin.cmple(max).all() {
+        return Some(0.0);
+    }
+
+    // rays parallel to an axis get infinite distances to its planes, which
+    // only leave them overlapping if they start between those planes
+    let inverse = direction.recip();
+    let to_min = (min - origin) * inverse;
+    let to_max = (max - origin) * inverse;
+    let enter = to_min.min(to_max).max_element();
+    let exit = to_min.max(to_max).min_element();
+    (enter >= 0.0 && enter <= exit).then_some(enter)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+
+    /// Generates the same tests for each backend, so that they're held to the
+    /// same query semantics.
+    macro_rules! backend_tests {
+        ($backend:ident, $new:expr) => {
+            mod $backend {
+                use super::*;
+
+                fn line_index() -> impl SpatialIndex<usize> {
+                    let mut index = $new;
+                    for id in 0..10 {
+                        index.insert(id, Vec2::new(id as f32, 0.0));
+                    }
+
+                    index.flush();
+                    index
+                }
+
+                #[test]
+                fn finds_the_k_nearest_within_the_radius() {
+                    let index = line_index();
+                    let hits = index.nearest_k(Vec2::new(4.2, 0.0), 3.0, 3, |_| true);
+                    let ids: Vec<usize> = hits.iter().map(|hit| hit.id).collect();
+                    assert_eq!(ids, [4, 5, 3]);
+
+                    let hits = index.nearest_k(Vec2::new(4.2, 0.0), 1.5, 10, |_| true);
+                    assert_eq!(hits.len(), 3);
+                    let hits = index.nearest_k(Vec2::new(4.2, 0.0), 1.5, 0, |_| true);
+                    assert!(hits.is_empty());
+                }
+
+                #[test]
+                fn filters_skip_entries() {
+                    let index = line_index();
+                    let hit = index.nearest(Vec2::new(4.2, 0.0), 3.0, |id| id % 2 == 1);
+                    assert_eq!(hit.map(|hit| hit.id), Some(5));
+                    assert_eq!(index.nearest(Vec2::ZERO, 3.0, |id| id > 5), None);
+                }
+
+                #[test]
+                fn finds_entries_inside_boxes() {
+                    let index = line_index();
+                    let min = Vec2::new(2.0, -1.0);
+                    let mut ids = index.within_aabb(min, Vec2::new(4.5, 1.0), |_| true);
+                    ids.sort();
+                    assert_eq!(ids, [2, 3, 4]);
+
+                    let min = Vec2::new(2.0, 0.5);
+                    assert!(index
+                        .within_aabb(min, Vec2::new(4.5, 1.0), |_| true)
+                        .is_empty());
+                }
+
+                #[test]
+                fn raycasts_hit_the_first_entry() {
+                    let index = line_index();
+                    let origin = Vec2::new(-2.0, 0.0);
+                    let hit = index.raycast(origin, Vec2::X, 20.0, 0.5, |id| id > 2);
+                    assert_eq!(
+                        hit,
+                        Some(Hit {
+                            id: 3,
+                            distance: 4.5
+                        })
+                    );
+
+                    let origin = Vec2::new(4.0, 2.0);
+                    let hit = index.raycast(origin, Vec2::NEG_Y, 1.0, 0.5, |_| true);
+                    assert_eq!(hit, None);
+                    let hit = index.raycast(origin, Vec2::NEG_Y, 2.0, 0.5, |_| true);
+                    assert_eq!(
+                        hit,
+                        Some(Hit {
+                            id: 4,
+                            distance: 1.5
+                        })
+                    );
+
+                    let origin = Vec2::new(4.2, 0.0);
+                    let hit = index.raycast(origin, Vec2::ZERO, 0.0, 0.5, |_| true);
+                    assert_eq!(hit.map(|hit| hit.id), Some(4));
+                }
+
+                #[test]
+                fn footprints_extend_entries() {
+                    let mut index = line_index();
+                    index.insert_with_footprint(20, Vec2::new(20.0, 0.0), Footprint::Circle(1.0));
+                    index.insert_with_footprint(
+                        30,
+                        Vec2::new(30.0, 0.0),
+                        Footprint::Box(Vec2::ONE),
+                    );
+                    index.flush();
+
+                    let hit = index.nearest(Vec2::new(18.5, 0.0), 1.0, |_| true);
+                    assert_eq!(
+                        hit,
+                        Some(Hit {
+                            id: 20,
+                            distance: 0.5
+                        })
+                    );
+                    let hit = index.nearest(Vec2::new(30.5, 0.5), 1.0, |_| true);
+                    assert_eq!(
+                        hit,
+                        Some(Hit {
+                            id: 30,
+                            distance: 0.0
+                        })
+                    );
+
+                    let ids =
+                        index.within_aabb(Vec2::new(28.0, 0.5), Vec2::new(29.5, 2.0), |_| true);
+                    assert_eq!(ids, [30]);
+                    let hit = index.raycast(Vec2::new(25.0, 0.0), Vec2::X, 10.0, 0.0, |_| true);
+                    assert_eq!(
+                        hit,
+                        Some(Hit {
+                            id: 30,
+                            distance: 4.0
+                        })
+                    );
+
+                    index.insert(30, Vec2::new(30.0, 0.0));
+                    assert_eq!(index.nearest(Vec2::new(30.5, 2.0), 1.0, |_| true), None);
+                }
+
+                #[test]
+                fn moved_and_removed_entries_are_updated() {
+                    let mut index = line_index();
+                    index.insert(9, Vec2::new(0.0, 1.0));
+                    assert!(index.remove(0));
+                    assert!(!index.remove(0));
+                    index.flush();
+
+                    assert!(!index.is_dirty());
+                    index.insert(9, Vec2::new(0.0, 1.0));
+                    assert!(!index.is_dirty());
+
+                    assert_eq!(index.len(), 9);
+                    assert!(index.within_radius(Vec2::ZERO, 0.5, |_| true).is_empty());
+                    let hit = index.nearest(Vec2::new(0.0, 1.5), 1.0, |_| true).unwrap();
+                    assert_eq!(
+                        hit,
+                        Hit {
+                            id: 9,
+                            distance: 0.5
+                        }
+                    );
+                }
+            }
+        };
+    }
+
+    backend_tests!(qbvh, QbvhIndex::new());
+    backend_tests!(grid, GridIndex::new(2.0));
+
+    /// Helper function to make a random entry with any kind of footprint.
+    fn random_entry(rng: &mut StdRng, id: usize) -> (Vec2, Footprint) {
+        let position = Vec2::new(rng.gen_range(-20.0..20.0), rng.gen_range(-20.0..20.0));
+        let footprint = match id % 3 {
+            0 => Footprint::Point,
+            1 => Footprint::Circle(rng.gen_range(0.0..2.0)),
+            _ => Footprint::Box(Vec2::new(rng.gen_range(0.0..2.0), rng.gen_range(0.0..2.0))),
+        };
+
+        (position, footprint)
+    }
+
+    /// Helper function to check that both backends answer random queries the
+    /// same way.
+    fn assert_backends_agree(
+        qbvh: &QbvhIndex<usize>,
+        grid: &GridIndex<usize>,
+        rng: &mut StdRng,
+        queries: usize,
+    ) {
+        for _ in 0..queries {
+            let position = Vec2::new(rng.gen_range(-25.0..25.0), rng.gen_range(-25.0..25.0));
+            let radius = rng.gen_range(0.0..8.0);
+            // entries that contain the position are all at distance zero
+            let mut from_qbvh = qbvh.within_radius(position, radius, |id| id % 4 != 0);
+            let mut from_grid = grid.within_radius(position, radius, |id| id % 4 != 0);
+            from_qbvh.sort_by_key(|hit| hit.id);
+            from_grid.sort_by_key(|hit| hit.id);
+            assert_eq!(from_qbvh, from_grid);
+
+            let mut from_qbvh = qbvh.within_aabb(position, position + radius, |_| true);
+            let mut from_grid = grid.within_aabb(position, position + radius, |_| true);
+            from_qbvh.sort();
+            from_grid.sort();
+            assert_eq!(from_qbvh, from_grid);
+
+            // rays that start inside of several entries hit any one of them
+            let direction = Vec2::from_angle(rng.gen_range(0.0..6.0));
+            let from_qbvh = qbvh.raycast(position, direction, radius * 4.0, 1.0, |_| true);
+            let from_grid = grid.raycast(position, direction, radius * 4.0, 1.0, |_| true);
+            assert_eq!(
+                from_qbvh.map(|hit| hit.distance),
+                from_grid.map(|hit| hit.distance)
+            );
+        }
+    }
+
+    #[test]
+    fn backends_agree() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut qbvh = QbvhIndex::new();
+        let mut grid = GridIndex::new(3.0);
+        for id in 0..500 {
+            let (position, footprint) = random_entry(&mut rng, id);
+            qbvh.insert_with_footprint(id, position, footprint);
+            grid.insert_with_footprint(id, position, footprint);
+        }
+
+        for id in 0..100 {
+            qbvh.remove(id * 5);
+            grid.remove(id * 5);
+        }
+
+        qbvh.flush();
+        grid.flush();
+        assert_backends_agree(&qbvh, &grid, &mut rng, 100);
+    }
+
+    #[test]
+    fn backends_agree_without_flushing() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut qbvh = QbvhIndex::new();
+        let mut grid = GridIndex::new(3.0);
+        for round in 0..20 {
+            for _ in 0..25 {
+                let id = rng.gen_range(0..200);
+                if rng.gen_bool(0.3) {
+                    assert_eq!(qbvh.remove(id), grid.remove(id));
+                } else {
+                    let (position, footprint) = random_entry(&mut rng, id);
+                    qbvh.insert_with_footprint(id, position, footprint);
+                    grid.insert_with_footprint(id, position, footprint);
+                }
+            }
+
+            assert_eq!(qbvh.len(), grid.len());
+            assert_backends_agree(&qbvh, &grid, &mut rng, 20);
+
+            // restructure every so often, leaving changes pending in between
+            if round % 5 == 4 {
+                qbvh.flush();
+                grid.flush();
+            }
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use glam::Vec2;
+use parry2d::{
+    bounding_volume::{Aabb, BoundingVolume},
+    math::Point,
+    partitioning::{Qbvh, QbvhUpdateWorkspace},
+};
+use slab::Slab;
+
+use super::{Footprint, SpatialIndex};
+
+/// The margin that the index loosens its bounding volumes by when refitting.
+const MARGIN: f32 = 0.01;
+
+/// A QBVH of entries, which refits and rebalances its tree on each flush.
+///
+/// Entries changed since the last flush are kept out of the tree's results
+/// and checked one by one instead, so that queries see them right away.
+pub struct QbvhIndex<T> {
+    qbvh: Qbvh<usize>,
+    leaves: Slab<(T, Vec2, Footprint)>,
+    ids_to_leaves: HashMap<T, usize>,
+    workspace: QbvhUpdateWorkspace,
+    pending: HashSet<usize>,
+    dirty: bool,
+}
+
+impl<T> Default for QbvhIndex<T> {
+    fn default() -> Self {
+        Self {
+            qbvh: Qbvh::new(),
+            leaves: Slab::new(),
+            ids_to_leaves: HashMap::new(),
+            workspace: QbvhUpdateWorkspace::default(),
+            pending: HashSet::new(),
+            dirty: false,
+        }
+    }
+}
+
+impl<T> QbvhIndex<T> {
+    /// Creates an empty index.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<T: Copy + Eq + Hash> SpatialIndex<T> for QbvhIndex<T> {
+    fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn insert_with_footprint(&mut self, id: T, position: Vec2, footprint: Footprint) {
+        let entry = (id, position, footprint);
+        let leaf = match self.ids_to_leaves.get(&id) {
+            Some(leaf) => {
+                let old_entry = &mut self.leaves[*leaf];
+                if *old_entry == entry {
+                    return;
+                }
+
+                *old_entry = entry;
+                *leaf
+            }
+            None => {
+                let leaf = self.leaves.insert(entry);
+                self.ids_to_leaves.insert(id, leaf);
+                leaf
+            }
+        };
+
+        self.qbvh.pre_update_or_insert(leaf);
+        self.pending.insert(leaf);
+        self.dirty = true;
+    }
+
+    fn remove(&mut self, id: T) -> bool {
+        let Some(leaf) = self.ids_to_leaves.remove(&id) else {
+            return false;
+        };
+
+        self.qbvh.remove(leaf);
+        self.leaves.remove(leaf);
+        self.pending.remove(&leaf);
+        self.dirty = true;
+        true
+    }
+
+    fn flush(&mut self) {
+        if !self.dirty {
+            return;
+        }
+
+        self.dirty = false;
+        self.pending.clear();
+        let leaves = &self.leaves;
+        self.qbvh.refit(MARGIN, &mut self.workspace, |leaf| {
+            leaves
+                .get(*leaf)
+                .map(|(_, position, footprint)| footprint_aabb(*position, *footprint))
+                .unwrap_or(Aabb::new_invalid())
+        });
+
+        self.qbvh.rebalance(MARGIN, &mut self.workspace);
+    }
+
+    fn candidates(&self, min: Vec2, max: Vec2) -> impl Iterator<Item = (T, Vec2, Footprint)> + '_ {
+        let aabb = Aabb::new(to_point(min), to_point(max));
+        let mut leaves = Vec::new();
+        self.qbvh.intersect_aabb(&aabb, &mut leaves);
+
+        // the tree's volumes for pending leaves may be out of date
+        let pending = self.pending.iter().copied().filter(move |leaf| {
+            let (_, position, footprint) = self.leaves[*leaf];
+            footprint_aabb(position, footprint).intersects(&aabb)
+        });
+
+        leaves
+            .into_iter()
+            .filter(|leaf| !self.pending.contains(leaf))
+            .chain(pending)
+            .filter_map(|leaf| self.leaves.get(leaf).copied())
+    }
+}
+
+/// Helper function to convert a position to a parry point.
+fn to_point(position: Vec2) -> Point<f32> {
+    Point::new(position.x, position.y)
+}
+
+/// Helper function to get the bounding volume of a footprint centered on a
+/// position.
+fn footprint_aabb(position: Vec2, footprint: Footprint) -> Aabb {
+    let half_extents = footprint.half_extents();
+    Aabb::new(
+        to_point(position - half_extents),
+        to_point(position + half_extents),
+    )
+}
@@ -0,0 +1,310 @@
+//! A* pathfinding over the tile graph.
+
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+    hash::Hash,
+};
+
+use glam::Vec2;
+
+use crate::map::OrdinalDirection;
+
+/// The maximum number of tiles that a single search expands before giving up.
+pub const MAX_EXPANDED: usize = 1024;
+
+/// Per-agent rules for which tiles an agent can walk on and what they cost.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PathRules {
+    /// The extra cost of walking through a tile with a medium crop on it.
+    /// Agents with an infinite cost never walk through medium crops.
+    pub medium_crop_cost: f32,
+}
+
+impl Default for PathRules {
+    fn default() -> Self {
+        Self {
+            medium_crop_cost: 0.0,
+        }
+    }
+}
+
+impl PathRules {
+    /// The cost of stepping onto a tile, or `None` if the agent can't walk on
+    /// it.
+    pub fn step_cost(&self, has_medium_crop: bool) -> Option<f32> {
+        let extra = if has_medium_crop {
+            self.medium_crop_cost
+        } else {
+            0.0
+        };
+
+        let cost = 1.0 + extra;
+        cost.is_finite().then_some(cost)
+    }
+
+    /// A key that distinguishes these rules from others in a [PathCache].
+    pub fn cache_key(&self) -> u32 {
+        self.medium_crop_cost.to_bits()
+    }
+}
+
+/// Finds the cheapest path between two tiles, including both ends.
+///
+/// `neighbor` looks up the neighbor of a tile in a given direction, if any.
+/// `step_cost` returns the cost of stepping onto a tile, or `None` if it can't
+/// be walked on. `estimate` must never overestimate the remaining cost from a
+/// tile to `goal`.
+pub fn find_path<N: Copy + Eq + Hash>(
+    start: N,
+    goal: N,
+    mut neighbor: impl FnMut(N, OrdinalDirection) -> Option<N>,
+    mut step_cost: impl FnMut(N) -> Option<f32>,
+    mut estimate: impl FnMut(N) -> f32,
+) -> Option<Vec<N>> {
+    let mut frontier = BinaryHeap::new();
+    let mut came_from: HashMap<N, N> = HashMap::new();
+    let mut costs: HashMap<N, f32> = HashMap::new();
+
+    frontier.push(Frontier {
+        priority: estimate(start),
+        node: start,
+    });
+
+    costs.insert(start, 0.0);
+
+    let mut expanded = 0;
+    while let Some(Frontier { node, priority }) = frontier.pop() {
+        if node == goal {
+            let mut path = vec![goal];
+            let mut current = goal;
+            while let Some(previous) = came_from.get(&current).copied() {
+                path.push(previous);
+                current = previous;
+            }
+
+            path.reverse();
+            return Some(path);
+        }
+
+        let cost = costs[&node];
+
+        // skip stale entries for nodes that have since been reached cheaper
+        if priority > cost + estimate(node) {
+            continue;
+        }
+
+        expanded += 1;
+        if expanded > MAX_EXPANDED {
+            return None;
+        }
+
+        for dir in OrdinalDirection::ALL {
+            let Some(next) = neighbor(node, dir) else {
+                continue;
+            };
+
+            let Some(step) = step_cost(next) else {
+                continue;
+            };
+
+            let next_cost = cost + step;
+            if costs.get(&next).is_some_and(|known| *known <= next_cost) {
+                continue;
+            }
+
+            costs.insert(next, next_cost);
+            came_from.insert(next, node);
+            frontier.push(Frontier {
+                priority: next_cost + estimate(next),
+                node: next,
+            });
+        }
+    }
+
+    None
+}
+
+/// The Manhattan distance between two tile positions, which never
+/// overestimates the cost of a path between them.
+pub fn tile_distance(a: Vec2, b: Vec2) -> f32 {
+    let delta = (a - b).abs();
+    delta.x + delta.y
+}
+
+/// Returns the tile that an agent following a path should head to next.
+pub fn next_waypoint<N: Copy>(path: &[N]) -> Option<N> {
+    path.get(1).or(path.first()).copied()
+}
+
+/// A cache of found paths, keyed by the agent's rules and its start and goal.
+///
+/// The cache must be cleared whenever the tile graph or its costs change.
+#[derive(Clone, Debug)]
+pub struct PathCache<K, N> {
+    paths: HashMap<(K, N, N), Option<Vec<N>>>,
+    capacity: usize,
+}
+
+impl<K: Copy + Eq + Hash, N: Copy + Eq + Hash> PathCache<K, N> {
+    /// Creates a cache that holds up to `capacity` paths before it's emptied.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            paths: HashMap::new(),
+            capacity,
+        }
+    }
+
+    /// Looks up a cached path, calling `find` to find it on a miss.
+    pub fn get_or_find(
+        &mut self,
+        key: K,
+        start: N,
+        goal: N,
+        find: impl FnOnce() -> Option<Vec<N>>,
+    ) -> Option<&[N]> {
+        let entry = (key, start, goal);
+        if !self.paths.contains_key(&entry) && self.paths.len() >= self.capacity {
+            self.paths.clear();
+        }
+
+        self.paths.entry(entry).or_insert_with(find).as_deref()
+    }
+
+    /// Forgets every cached path.
+    pub fn clear(&mut self) {
+        self.paths.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+}
+
+/// An entry in the A* frontier, ordered so that the lowest priority pops first.
+struct Frontier<N> {
+    priority: f32,
+    node: N,
+}
+
+impl<N> PartialEq for Frontier<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl<N> Eq for Frontier<N> {}
+
+impl<N> PartialOrd for Frontier<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N> Ord for Frontier<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.total_cmp(&self.priority)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::IVec2;
+
+    use super::*;
+    use crate::map::TileMap;
+
+    /// Helper function to find a path on a map of walls (`true`) and floors.
+    fn find(map: &TileMap<bool>, start: IVec2, goal: IVec2) -> Option<Vec<IVec2>> {
+        find_path(
+            start,
+            goal,
+            |xy, dir| map.neighbor(xy, dir),
+            |xy| (!map.get(xy)?).then_some(1.0),
+            |xy| tile_distance(xy.as_vec2(), goal.as_vec2()),
+        )
+    }
+
+    #[test]
+    fn paths_go_straight_on_open_ground() {
+        let map = TileMap::new(IVec2::new(5, 5), |_| false);
+        let path = find(&map, IVec2::new(0, 2), IVec2::new(4, 2)).unwrap();
+        let expected: Vec<_> = (0..5).map(|x| IVec2::new(x, 2)).collect();
+        assert_eq!(path, expected);
+        assert_eq!(next_waypoint(&path), Some(IVec2::new(1, 2)));
+
+        let path = find(&map, IVec2::ONE, IVec2::ONE).unwrap();
+        assert_eq!(path, vec![IVec2::ONE]);
+        assert_eq!(next_waypoint(&path), Some(IVec2::ONE));
+    }
+
+    #[test]
+    fn paths_go_around_walls() {
+        // a wall down the middle column with a gap at the bottom
+        let map = TileMap::new(IVec2::new(5, 5), |xy| xy.x == 2 && xy.y < 4);
+        let path = find(&map, IVec2::new(0, 0), IVec2::new(4, 0)).unwrap();
+        assert!(path.contains(&IVec2::new(2, 4)));
+        assert!(path.iter().all(|xy| !map.get(*xy).unwrap()));
+        assert_eq!(path.len(), 13);
+
+        // close the gap
+        let map = TileMap::new(IVec2::new(5, 5), |xy| xy.x == 2);
+        assert_eq!(find(&map, IVec2::new(0, 0), IVec2::new(4, 0)), None);
+    }
+
+    #[test]
+    fn paths_prefer_cheaper_tiles() {
+        let crops = TileMap::new(IVec2::new(5, 3), |xy| xy.y == 1 && xy.x > 0 && xy.x < 4);
+        let rules = PathRules {
+            medium_crop_cost: 10.0,
+        };
+
+        let goal = IVec2::new(4, 1);
+        let path = find_path(
+            IVec2::new(0, 1),
+            goal,
+            |xy, dir| crops.neighbor(xy, dir),
+            |xy| rules.step_cost(*crops.get(xy)?),
+            |xy| tile_distance(xy.as_vec2(), goal.as_vec2()),
+        )
+        .unwrap();
+
+        assert!(path.iter().all(|xy| !crops.get(*xy).unwrap()));
+        assert_eq!(path.len(), 7);
+
+        let blocked = PathRules {
+            medium_crop_cost: f32::INFINITY,
+        };
+        assert_eq!(blocked.step_cost(true), None);
+        assert_eq!(PathRules::default().step_cost(true), Some(1.0));
+    }
+
+    #[test]
+    fn caches_only_search_once() {
+        let mut cache = PathCache::new(2);
+        let mut searches = 0;
+        for _ in 0..3 {
+            let path = cache.get_or_find(0, 1, 3, || {
+                searches += 1;
+                Some(vec![1, 2, 3])
+            });
+            assert_eq!(path, Some(&[1, 2, 3][..]));
+        }
+
+        assert_eq!(searches, 1);
+
+        assert_eq!(cache.get_or_find(1, 1, 3, || None), None);
+        assert_eq!(cache.len(), 2);
+
+        // a full cache empties itself before caching another path
+        cache.get_or_find(0, 2, 3, || Some(vec![2, 3]));
+        assert_eq!(cache.len(), 1);
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
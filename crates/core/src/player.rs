@@ -0,0 +1,676 @@
+//! Player movement, shared by the server that simulates it and the clients
+//! that predict it.
+
+use std::collections::VecDeque;
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+use glam::{IVec2, Mat2, Vec2};
+
+use crate::{map::position_to_tile, partitioning::Footprint};
+
+/// The speed in meters per second that players move at unless they're given
+/// another.
+pub const DEFAULT_MOVEMENT_SPEED: f32 = 4.0;
+
+/// The longest time in seconds that a single movement input can last, so that
+/// a hitch doesn't move a player too far at once.
+pub const MAX_INPUT_DT: f32 = 0.1;
+
+/// The most time in seconds of movement that the server banks for each player,
+/// so that inputs delayed by the network can still catch up.
+///
+/// Players can't move for longer than the time that has passed plus this,
+/// however many inputs their clients send.
+pub const MAX_MOVEMENT_BUDGET: f32 = 0.25;
+
+/// The longest time in seconds that clients merge frames with the same
+/// movement direction into one input before sending it.
+pub const INPUT_INTERVAL: f32 = 0.05;
+
+/// The number of steps that yaws and pitches are quantized to.
+const LOOK_STEPS: f32 = 65536.0;
+
+/// The radius of the circle that players take up when colliding with tiles.
+pub const PLAYER_RADIUS: f32 = 0.3;
+
+/// The number of times that a blocked movement is halved to find how far a
+/// player can get before colliding.
+const COLLISION_ITERATIONS: usize = 8;
+
+/// The most unacknowledged inputs that clients send with each new input, so
+/// that inputs lost on the way still reach the server.
+pub const MAX_RESENT_INPUTS: usize = 8;
+
+/// The most inputs that a client keeps waiting for the server to apply.
+/// Older inputs are forgotten, so a stalled server can't grow them forever.
+pub const MAX_PENDING_INPUTS: usize = 256;
+
+/// Packs a yaw and a pitch in radians into 16 bits each, for sending over the
+/// network.
+pub fn pack_look(yaw: f32, pitch: f32) -> u32 {
+    let yaw = (yaw.rem_euclid(TAU) / TAU * LOOK_STEPS).round() as u32 % LOOK_STEPS as u32;
+    let pitch = pitch.clamp(-FRAC_PI_2, FRAC_PI_2) + FRAC_PI_2;
+    let pitch = (pitch / PI * (LOOK_STEPS - 1.0)).round() as u32;
+    yaw << 16 | pitch
+}
+
+/// Unpacks a yaw from zero to [TAU] and a pitch from -[FRAC_PI_2] to
+/// [FRAC_PI_2] that were packed by [pack_look].
+pub fn unpack_look(look: u32) -> (f32, f32) {
+    let yaw = (look >> 16) as f32 / LOOK_STEPS * TAU;
+    let pitch = (look & 0xffff) as f32 / (LOOK_STEPS - 1.0) * PI - FRAC_PI_2;
+    (yaw, pitch)
+}
+
+/// Numbers a batch of inputs that were sent together, ending with the input
+/// numbered `sequence`.
+///
+/// Only the newest [MAX_RESENT_INPUTS] inputs are kept, and batches that don't
+/// add up are discarded.
+pub fn unpack_inputs(
+    sequence: u32,
+    directions: &[Vec2],
+    dts: &[f32],
+    looks: &[u32],
+) -> Vec<MovementInput> {
+    if directions.len() != dts.len() || dts.len() != looks.len() {
+        return Vec::new();
+    }
+
+    let skipped = directions.len().saturating_sub(MAX_RESENT_INPUTS);
+    let count = (directions.len() - skipped) as u32;
+    let Some(first) = sequence
+        .checked_add(1)
+        .and_then(|next| next.checked_sub(count))
+        .filter(|first| *first > 0)
+    else {
+        return Vec::new();
+    };
+
+    directions
+        .iter()
+        .zip(dts)
+        .zip(looks)
+        .skip(skipped)
+        .zip(first..)
+        .map(|(((direction, dt), look), sequence)| {
+            let (yaw, pitch) = unpack_look(*look);
+            MovementInput::new(sequence, *direction, yaw, pitch, *dt)
+        })
+        .collect()
+}
+
+/// The input from a player over one or more frames.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MovementInput {
+    /// The order that the input was sent in, starting from one.
+    pub sequence: u32,
+
+    /// The movement direction relative to the yaw, normalized or zero.
+    pub direction: Vec2,
+
+    /// The yaw in radians that the player is facing.
+    pub yaw: f32,
+
+    /// The pitch in radians that the player is looking up or down at.
+    pub pitch: f32,
+
+    /// The time in seconds that this input lasted.
+    pub dt: f32,
+}
+
+impl MovementInput {
+    /// Creates an input, cleaning up a raw direction, look, and duration so
+    /// that clients and the server move by exactly the same amount.
+    ///
+    /// Yaws and pitches are quantized like [pack_look], so that they're the
+    /// same after a trip over the network.
+    pub fn new(sequence: u32, direction: Vec2, yaw: f32, pitch: f32, dt: f32) -> Self {
+        let (yaw, pitch) = unpack_look(pack_look(yaw, pitch));
+        Self {
+            sequence,
+            direction: direction.normalize_or_zero(),
+            yaw,
+            pitch,
+            dt: if dt.is_nan() {
+                0.0
+            } else {
+                dt.clamp(0.0, MAX_INPUT_DT)
+            },
+        }
+    }
+
+    /// Shortens this input to fit within a movement budget in seconds, and
+    /// returns what's left of the budget.
+    pub fn limit(&mut self, budget: f32) -> f32 {
+        let budget = budget.max(0.0);
+        self.dt = self.dt.min(budget);
+        budget - self.dt
+    }
+
+    /// This input's yaw and pitch packed by [pack_look].
+    pub fn look(&self) -> u32 {
+        pack_look(self.yaw, self.pitch)
+    }
+
+    /// Returns a map position moved by this input at a speed in meters per
+    /// second.
+    ///
+    /// `is_blocked` tells whether players can't enter a tile. Players slide
+    /// along the blocked tiles that they run into, and can always leave the
+    /// ones that they're already overlapping.
+    pub fn apply(&self, position: Vec2, speed: f32, is_blocked: impl Fn(IVec2) -> bool) -> Vec2 {
+        let delta = Mat2::from_angle(self.yaw) * self.direction * speed * self.dt;
+        slide(position, delta, &is_blocked)
+    }
+}
+
+/// Returns a movement budget in seconds refilled by a frame's time, up to
+/// [MAX_MOVEMENT_BUDGET].
+pub fn refill_budget(budget: f32, dt: f32) -> f32 {
+    if dt.is_nan() {
+        return budget;
+    }
+
+    (budget + dt.max(0.0)).min(MAX_MOVEMENT_BUDGET)
+}
+
+/// Helper function to move a player along each axis in turn, so that it keeps
+/// moving along whichever axis isn't blocked.
+fn slide(mut position: Vec2, delta: Vec2, is_blocked: &impl Fn(IVec2) -> bool) -> Vec2 {
+    for step in [delta * Vec2::X, delta * Vec2::Y] {
+        if !collides(position, position + step, is_blocked) {
+            position += step;
+            continue;
+        }
+
+        // search for the furthest that the player can get along this axis
+        let (mut clear, mut blocked) = (0.0, 1.0);
+        for _ in 0..COLLISION_ITERATIONS {
+            let middle = (clear + blocked) / 2.0;
+            if collides(position, position + step * middle, is_blocked) {
+                blocked = middle;
+            } else {
+                clear = middle;
+            }
+        }
+
+        position += step * clear;
+    }
+
+    position
+}
+
+/// Helper function to check if moving a player runs it into a blocked tile
+/// that it wasn't already overlapping.
+fn collides(from: Vec2, to: Vec2, is_blocked: &impl Fn(IVec2) -> bool) -> bool {
+    let tile = Footprint::Box(Vec2::splat(0.5));
+    let overlaps =
+        |position: Vec2, xy: IVec2| tile.distance(xy.as_vec2(), position) < PLAYER_RADIUS;
+
+    let min = position_to_tile(to - PLAYER_RADIUS);
+    let max = position_to_tile(to + PLAYER_RADIUS);
+    (min.y..=max.y)
+        .flat_map(|y| (min.x..=max.x).map(move |x| IVec2::new(x, y)))
+        .any(|xy| overlaps(to, xy) && !overlaps(from, xy) && is_blocked(xy))
+}
+
+/// A client's prediction of its own player's position, ahead of the server.
+///
+/// Frames are merged into inputs, which are sent once their movement direction
+/// changes or they've lasted [INPUT_INTERVAL]. Sent inputs wait until the
+/// server reports that it has applied them, and are replayed on top of each
+/// authoritative position and sent again with newer inputs, or every
+/// [INPUT_INTERVAL] while there are no newer ones, until then.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Prediction {
+    /// The predicted map position.
+    pub position: Vec2,
+
+    authoritative: Vec2,
+    pending: VecDeque<MovementInput>,
+    unsent: Option<MovementInput>,
+    last_sent: Option<MovementInput>,
+    since_sent: f32,
+}
+
+impl Prediction {
+    /// Starts predicting from a map position.
+    pub fn new(position: Vec2) -> Self {
+        Self {
+            position,
+            authoritative: position,
+            ..Default::default()
+        }
+    }
+
+    /// The number of sent inputs that the server hasn't applied yet.
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// The newest inputs that the server hasn't applied yet, up to
+    /// [MAX_RESENT_INPUTS] and oldest first. These are sent together whenever
+    /// [Prediction::predict] returns an input.
+    pub fn unacknowledged(&self) -> impl Iterator<Item = &MovementInput> {
+        let skipped = self.pending.len().saturating_sub(MAX_RESENT_INPUTS);
+        self.pending.iter().skip(skipped)
+    }
+
+    /// Moves the prediction by a frame of input, returning the newest input to
+    /// send to the server if one is ready, or if the server still hasn't
+    /// applied the last one after [INPUT_INTERVAL].
+    ///
+    /// Predictions move like [MovementInput::apply], so they should be given
+    /// the same speed and blocked tiles that the server sees.
+    pub fn predict(
+        &mut self,
+        direction: Vec2,
+        yaw: f32,
+        pitch: f32,
+        dt: f32,
+        speed: f32,
+        is_blocked: impl Fn(IVec2) -> bool,
+    ) -> Option<MovementInput> {
+        let frame = MovementInput::new(0, direction, yaw, pitch, dt);
+        self.since_sent += frame.dt;
+        let mut sent = None;
+        match &mut self.unsent {
+            Some(unsent)
+                if unsent.direction == frame.direction
+                    && unsent.dt + frame.dt <= INPUT_INTERVAL =>
+            {
+                unsent.yaw = frame.yaw;
+                unsent.pitch = frame.pitch;
+                unsent.dt += frame.dt;
+            }
+            _ => {
+                sent = self.send();
+
+                // standing still without looking around tells the server nothing
+                let last_look = self.last_sent.map(|input| input.look());
+                if frame.direction != Vec2::ZERO || last_look != Some(frame.look()) {
+                    self.unsent = Some(frame);
+                }
+            }
+        }
+
+        // the last inputs may have been lost, and standing still won't send
+        // newer inputs to make up for them
+        if sent.is_none() && self.since_sent >= INPUT_INTERVAL {
+            sent = self.pending.back().copied();
+        }
+
+        if sent.is_some() {
+            self.since_sent = 0.0;
+        }
+
+        self.replay(speed, &is_blocked);
+        sent
+    }
+
+    /// Corrects the prediction with the server's position after it applied
+    /// every input up to and including `sequence`.
+    pub fn reconcile(
+        &mut self,
+        position: Vec2,
+        sequence: u32,
+        speed: f32,
+        is_blocked: impl Fn(IVec2) -> bool,
+    ) {
+        while self
+            .pending
+            .front()
+            .is_some_and(|input| input.sequence <= sequence)
+        {
+            self.pending.pop_front();
+        }
+
+        self.authoritative = position;
+        self.replay(speed, &is_blocked);
+    }
+
+    /// Helper function to number the unsent input and wait for the server to
+    /// apply it.
+    fn send(&mut self) -> Option<MovementInput> {
+        let mut input = self.unsent.take()?;
+        input.sequence = self.last_sent.map_or(1, |last| last.sequence + 1);
+
+        if self.pending.len() == MAX_PENDING_INPUTS {
+            self.pending.pop_front();
+        }
+
+        self.pending.push_back(input);
+        self.last_sent = Some(input);
+        Some(input)
+    }
+
+    /// Helper function to predict the position from the last authoritative
+    /// one and every input since.
+    fn replay(&mut self, speed: f32, is_blocked: &impl Fn(IVec2) -> bool) {
+        self.position = self
+            .pending
+            .iter()
+            .chain(&self.unsent)
+            .fold(self.authoritative, |position, input| {
+                input.apply(position, speed, is_blocked)
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A test map with a wall of blocked tiles at x = 3, inside of a map
+    /// that's 8 tiles wide.
+    fn is_blocked(xy: IVec2) -> bool {
+        xy.x == 3 || !(0..8).contains(&xy.x) || !(0..8).contains(&xy.y)
+    }
+
+    fn walk(direction: Vec2, dt: f32) -> MovementInput {
+        MovementInput::new(1, direction, 0.0, 0.0, dt)
+    }
+
+    /// A frame time that adds up exactly, so that merged frames fill inputs
+    /// predictably.
+    const FRAME: f32 = 1.0 / 64.0;
+
+    #[test]
+    fn inputs_are_cleaned_up() {
+        let input = MovementInput::new(1, Vec2::new(3.0, 4.0), TAU + 1.0, 2.0, 1.0);
+        assert_eq!(input.direction, Vec2::new(0.6, 0.8));
+        assert!((input.yaw - 1.0).abs() < 1e-4);
+        assert_eq!(input.pitch, FRAC_PI_2);
+        assert_eq!(input.dt, MAX_INPUT_DT);
+
+        let input = MovementInput::new(1, Vec2::new(f32::NAN, 0.0), 0.0, f32::NAN, f32::NAN);
+        assert_eq!(input.dt, 0.0);
+        assert_eq!(input.apply(Vec2::ONE, 4.0, |_| false), Vec2::ONE);
+    }
+
+    #[test]
+    fn looks_are_the_same_after_packing() {
+        for (yaw, pitch) in [(0.0, 0.0), (-1.0, 0.3), (3.0, -FRAC_PI_2), (TAU, 1.0)] {
+            let input = MovementInput::new(1, Vec2::X, yaw, pitch, FRAME);
+            let (packed_yaw, packed_pitch) = unpack_look(input.look());
+            assert_eq!((packed_yaw, packed_pitch), (input.yaw, input.pitch));
+
+            let repacked = MovementInput::new(1, Vec2::X, packed_yaw, packed_pitch, FRAME);
+            assert_eq!(repacked, input);
+        }
+    }
+
+    #[test]
+    fn movement_scales_with_time() {
+        let position = Vec2::new(1.0, 1.0);
+        let moved = walk(Vec2::Y, 0.05).apply(position, 4.0, is_blocked);
+        assert!(moved.abs_diff_eq(Vec2::new(1.0, 1.2), 1e-5));
+
+        let halves = walk(Vec2::Y, 0.025);
+        let moved_twice = halves.apply(halves.apply(position, 4.0, is_blocked), 4.0, is_blocked);
+        assert!(moved.abs_diff_eq(moved_twice, 1e-5));
+    }
+
+    #[test]
+    fn flooded_inputs_are_limited_by_elapsed_time() {
+        let mut position = Vec2::ZERO;
+        let mut budget = 0.0;
+        let mut sequence = 0;
+        let frames = 30;
+        for _ in 0..frames {
+            budget = refill_budget(budget, FRAME);
+            for _ in 0..100 {
+                sequence += 1;
+                let mut input = MovementInput::new(sequence, Vec2::X, 0.0, 0.0, MAX_INPUT_DT);
+                budget = input.limit(budget);
+                position = input.apply(position, 4.0, |_| false);
+            }
+        }
+
+        let elapsed = frames as f32 * FRAME;
+        assert!(position.x <= 4.0 * elapsed + 1e-4);
+        assert!(position.x >= 4.0 * elapsed - 1e-4);
+
+        // idle time is only banked up to a limit
+        let budget = refill_budget(0.0, 10.0);
+        assert_eq!(budget, MAX_MOVEMENT_BUDGET);
+        assert_eq!(refill_budget(budget, f32::NAN), budget);
+    }
+
+    #[test]
+    fn blocked_tiles_stop_and_slide_players() {
+        // walls stop players just short of them
+        let position = Vec2::new(2.0, 4.0);
+        let moved = walk(Vec2::X, 0.1).apply(position, 10.0, is_blocked);
+        assert!(moved.x < 2.5 - PLAYER_RADIUS && moved.x > 2.49 - PLAYER_RADIUS);
+
+        // and let them slide along instead of sticking
+        let moved = walk(Vec2::ONE, 0.1).apply(position, 10.0, is_blocked);
+        assert!(moved.y > 4.5);
+
+        // players can walk out of a tile that was blocked under them
+        let moved = walk(Vec2::NEG_X, 0.1).apply(Vec2::new(3.0, 4.0), 4.0, is_blocked);
+        assert!(moved.x < 3.0);
+    }
+
+    #[test]
+    fn players_stay_on_the_map() {
+        let mut position = Vec2::new(1.0, 1.0);
+        for _ in 0..20 {
+            position = walk(Vec2::NEG_ONE, 0.1).apply(position, 4.0, is_blocked);
+        }
+
+        assert!(position
+            .cmpge(Vec2::splat(-0.5 + PLAYER_RADIUS - 0.01))
+            .all());
+        assert_eq!(position_to_tile(position), IVec2::ZERO);
+    }
+
+    #[test]
+    fn frames_are_merged_until_the_direction_changes() {
+        let mut prediction = Prediction::new(Vec2::ZERO);
+        for _ in 0..3 {
+            assert_eq!(
+                prediction.predict(Vec2::X, 0.0, 0.0, FRAME, 4.0, |_| false),
+                None
+            );
+        }
+
+        // the prediction moves every frame, even before inputs are sent
+        assert!(prediction
+            .position
+            .abs_diff_eq(Vec2::new(4.0 * 3.0 * FRAME, 0.0), 1e-5));
+
+        let sent = prediction.predict(Vec2::X, 0.0, 0.0, FRAME, 4.0, |_| false);
+        assert_eq!(
+            sent.map(|input| (input.sequence, input.dt)),
+            Some((1, 3.0 * FRAME))
+        );
+
+        let sent = prediction.predict(Vec2::Y, 0.0, 0.0, FRAME, 4.0, |_| false);
+        assert_eq!(
+            sent.map(|input| (input.sequence, input.dt)),
+            Some((2, FRAME))
+        );
+        assert_eq!(prediction.pending_len(), 2);
+    }
+
+    #[test]
+    fn standing_still_sends_nothing_new() {
+        let mut prediction = Prediction::new(Vec2::ZERO);
+        prediction.predict(Vec2::ZERO, 1.0, 0.0, FRAME, 4.0, |_| false);
+        let sent = prediction.predict(Vec2::X, 1.0, 0.0, FRAME, 4.0, |_| false);
+        assert!(sent.is_some());
+
+        let sent = prediction.predict(Vec2::ZERO, 1.0, 0.0, FRAME, 4.0, |_| false);
+        assert!(sent.is_some());
+
+        // once the server has applied everything
+        prediction.reconcile(prediction.position, 2, 4.0, |_| false);
+        for _ in 0..20 {
+            let sent = prediction.predict(Vec2::ZERO, 1.0, 0.0, FRAME, 4.0, |_| false);
+            assert_eq!(sent, None);
+        }
+
+        // looking around is still sent
+        prediction.predict(Vec2::ZERO, 2.0, 0.0, FRAME, 4.0, |_| false);
+        let sent = prediction.predict(Vec2::X, 2.0, 0.0, FRAME, 4.0, |_| false);
+        assert_eq!(sent.map(|input| input.direction), Some(Vec2::ZERO));
+    }
+
+    #[test]
+    fn reconciling_replays_unapplied_inputs() {
+        let mut client = Prediction::new(Vec2::ZERO);
+        let mut sent = Vec::new();
+        for frame in 0..12 {
+            let direction = if frame % 2 == 0 { Vec2::X } else { Vec2::Y };
+            sent.extend(client.predict(direction, 0.0, 0.0, FRAME, 2.0, |_| false));
+        }
+
+        let predicted = client.position;
+        assert_eq!(sent.len(), 11);
+
+        // the server catches up on some of the inputs
+        let mut server = Vec2::ZERO;
+        for input in &sent[..8] {
+            server = input.apply(server, 2.0, |_| false);
+        }
+
+        client.reconcile(server, sent[7].sequence, 2.0, |_| false);
+        assert_eq!(client.pending_len(), 3);
+        assert!(client.position.abs_diff_eq(predicted, 1e-5));
+
+        // the server disagrees, so the prediction is corrected
+        client.reconcile(Vec2::new(0.0, 1.0), sent[10].sequence, 2.0, |_| false);
+        assert_eq!(client.pending_len(), 0);
+        let unsent = Vec2::new(0.0, 2.0 * FRAME);
+        assert!(client
+            .position
+            .abs_diff_eq(Vec2::new(0.0, 1.0) + unsent, 1e-5));
+    }
+
+    #[test]
+    fn lost_inputs_are_resent() {
+        let mut client = Prediction::new(Vec2::ZERO);
+        let mut server = Vec2::ZERO;
+        let mut applied = 0;
+        for frame in 0..40 {
+            let direction = if frame % 2 == 0 { Vec2::X } else { Vec2::Y };
+            let Some(input) = client.predict(direction, 0.5, 0.0, FRAME, 2.0, |_| false) else {
+                continue;
+            };
+
+            let batch: Vec<_> = client.unacknowledged().copied().collect();
+            assert_eq!(batch.last(), Some(&input));
+
+            // most messages are lost on the way to the server
+            if input.sequence % 3 != 0 {
+                continue;
+            }
+
+            let directions: Vec<_> = batch.iter().map(|input| input.direction).collect();
+            let dts: Vec<_> = batch.iter().map(|input| input.dt).collect();
+            let looks: Vec<_> = batch.iter().map(|input| input.look()).collect();
+            for input in unpack_inputs(input.sequence, &directions, &dts, &looks) {
+                if input.sequence > applied {
+                    server = input.apply(server, 2.0, |_| false);
+                    applied = input.sequence;
+                }
+            }
+
+            // the client doesn't jump back once the server catches up
+            let predicted = client.position;
+            client.reconcile(server, applied, 2.0, |_| false);
+            assert!(client.position.abs_diff_eq(predicted, 1e-5));
+        }
+
+        assert!(applied > 0);
+        assert!(client.unacknowledged().count() <= MAX_RESENT_INPUTS);
+    }
+
+    #[test]
+    fn inputs_are_resent_after_stopping() {
+        let mut client = Prediction::new(Vec2::ZERO);
+        let mut server = Vec2::ZERO;
+        let mut applied = 0;
+        let deliver = |client: &mut Prediction, server: &mut Vec2, applied: &mut u32| {
+            for input in client.unacknowledged() {
+                if input.sequence > *applied {
+                    *server = input.apply(*server, 2.0, |_| false);
+                    *applied = input.sequence;
+                }
+            }
+
+            client.reconcile(*server, *applied, 2.0, |_| false);
+        };
+
+        for _ in 0..10 {
+            if client
+                .predict(Vec2::X, 0.0, 0.0, FRAME, 2.0, |_| false)
+                .is_some()
+            {
+                deliver(&mut client, &mut server, &mut applied);
+            }
+        }
+
+        // the batch sent when the player stops is lost
+        let lost = client.predict(Vec2::ZERO, 0.0, 0.0, FRAME, 2.0, |_| false);
+        assert!(lost.is_some());
+        assert_ne!(client.position, server);
+
+        // standing still sends it again until the server applies it
+        for _ in 0..10 {
+            if client
+                .predict(Vec2::ZERO, 0.0, 0.0, FRAME, 2.0, |_| false)
+                .is_some()
+            {
+                deliver(&mut client, &mut server, &mut applied);
+            }
+        }
+
+        assert_eq!(client.pending_len(), 0);
+        assert!(client.position.abs_diff_eq(server, 1e-5));
+        assert!(server.abs_diff_eq(Vec2::new(2.0 * 10.0 * FRAME, 0.0), 1e-5));
+        assert_eq!(
+            client.predict(Vec2::ZERO, 0.0, 0.0, 1.0, 2.0, |_| false),
+            None
+        );
+    }
+
+    #[test]
+    fn batches_are_numbered_from_the_end() {
+        let inputs = unpack_inputs(5, &[Vec2::X, Vec2::Y], &[0.01, 0.02], &[0, 0]);
+        let numbered: Vec<_> = inputs
+            .iter()
+            .map(|input| (input.sequence, input.dt))
+            .collect();
+        assert_eq!(numbered, vec![(4, 0.01), (5, 0.02)]);
+
+        // only the newest inputs of oversized batches are kept
+        let count = MAX_RESENT_INPUTS + 2;
+        let inputs = unpack_inputs(
+            100,
+            &vec![Vec2::X; count],
+            &vec![0.01; count],
+            &vec![0; count],
+        );
+        assert_eq!(inputs.len(), MAX_RESENT_INPUTS);
+        assert_eq!(inputs[0].sequence, 101 - MAX_RESENT_INPUTS as u32);
+
+        // mismatched or impossible batches are discarded
+        assert!(unpack_inputs(5, &[Vec2::X], &[0.01, 0.02], &[0]).is_empty());
+        assert!(unpack_inputs(1, &[Vec2::X, Vec2::Y], &[0.01, 0.02], &[0, 0]).is_empty());
+    }
+
+    #[test]
+    fn pending_inputs_are_capped() {
+        let mut prediction = Prediction::default();
+        for frame in 0..MAX_PENDING_INPUTS + 10 {
+            let direction = if frame % 2 == 0 { Vec2::X } else { Vec2::Y };
+            prediction.predict(direction, 0.0, 0.0, FRAME, 1.0, |_| false);
+        }
+
+        assert_eq!(prediction.pending_len(), MAX_PENDING_INPUTS);
+    }
+}
@@ -0,0 +1,75 @@
+//! Rules for pollinators and the hives that they live in.
+
+/// The time in seconds that a crop stays pollinated after a pollinator visits
+/// it.
+pub const POLLINATION_DURATION: f32 = 10.0;
+
+/// Wears `dt` seconds off of a crop's remaining pollination, returning the
+/// time that it stays pollinated for, or `None` once it has worn off.
+pub fn wear_off_pollination(remaining: f32, dt: f32) -> Option<f32> {
+    let remaining = remaining - dt;
+    (remaining > 0.0).then_some(remaining)
+}
+
+/// The per-structure parameters of a pollinator hive.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HiveConfig {
+    /// The time in seconds between pollinator spawns.
+    pub spawn_interval: f32,
+
+    /// The maximum number of living pollinators that a hive supports.
+    pub capacity: usize,
+}
+
+/// The changing state of a single hive.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Hive {
+    /// The time in seconds until this hive can spawn another pollinator.
+    pub spawn_cooldown: f32,
+}
+
+impl Hive {
+    /// Advances a hive by `dt` seconds and returns whether it spawns a
+    /// pollinator, given the number of its pollinators that are still alive.
+    pub fn tick(&mut self, config: &HiveConfig, dt: f32, population: usize) -> bool {
+        self.spawn_cooldown = (self.spawn_cooldown - dt).max(0.0);
+        if self.spawn_cooldown > 0.0 || population >= config.capacity {
+            return false;
+        }
+
+        self.spawn_cooldown = config.spawn_interval;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: HiveConfig = HiveConfig {
+        spawn_interval: 5.0,
+        capacity: 2,
+    };
+
+    #[test]
+    fn hives_spawn_on_an_interval() {
+        let mut hive = Hive::default();
+        assert!(hive.tick(&CONFIG, 1.0, 0));
+        assert!(!hive.tick(&CONFIG, 1.0, 1));
+        assert!(!hive.tick(&CONFIG, 3.0, 1));
+        assert!(hive.tick(&CONFIG, 1.0, 1));
+    }
+
+    #[test]
+    fn pollination_wears_off() {
+        assert_eq!(wear_off_pollination(POLLINATION_DURATION, 4.0), Some(6.0));
+        assert_eq!(wear_off_pollination(1.0, 1.0), None);
+    }
+
+    #[test]
+    fn full_hives_stop_spawning() {
+        let mut hive = Hive::default();
+        assert!(!hive.tick(&CONFIG, 10.0, 2));
+        assert!(hive.tick(&CONFIG, 0.0, 1));
+    }
+}
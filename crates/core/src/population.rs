@@ -0,0 +1,282 @@
+//! Aging and breeding rules for fauna populations.
+
+use std::{collections::HashMap, hash::Hash};
+
+use glam::Vec2;
+
+use crate::fauna::FaunaState;
+
+/// The per-species parameters of a fauna's life cycle.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LifeCycle {
+    /// The age in seconds at which a fauna becomes an adult.
+    pub maturity_age: f32,
+
+    /// The age in seconds at which a fauna dies of old age.
+    pub max_age: f32,
+
+    /// The minimum fullness that an adult needs to breed.
+    pub breeding_fullness: f32,
+
+    /// The fullness that each parent gives up to their offspring.
+    pub breeding_cost: f32,
+
+    /// The time in seconds that a parent must wait before breeding again.
+    pub breeding_interval: f32,
+
+    /// The maximum distance between two mates.
+    pub mate_radius: f32,
+
+    /// The radius in which a fauna that is ready to breed looks for a mate.
+    pub mate_search_radius: f32,
+}
+
+/// The changing life cycle state of a single fauna.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Lifetime {
+    /// The age of this fauna in seconds.
+    pub age: f32,
+
+    /// The time in seconds until this fauna can breed again.
+    pub breeding_cooldown: f32,
+}
+
+impl Lifetime {
+    /// Ages a fauna by `dt` seconds.
+    pub fn grow_older(&mut self, dt: f32) {
+        self.age += dt;
+        self.breeding_cooldown = (self.breeding_cooldown - dt).max(0.0);
+    }
+
+    /// Whether this fauna has died of old age.
+    pub fn is_expired(&self, life_cycle: &LifeCycle) -> bool {
+        self.age >= life_cycle.max_age
+    }
+
+    /// Whether this fauna is an adult.
+    pub fn is_mature(&self, life_cycle: &LifeCycle) -> bool {
+        self.age >= life_cycle.maturity_age
+    }
+
+    /// Whether this fauna is a well-fed adult that can breed right now.
+    pub fn can_breed(&self, life_cycle: &LifeCycle, state: &FaunaState) -> bool {
+        self.is_mature(life_cycle)
+            && self.breeding_cooldown <= 0.0
+            && state.fullness >= life_cycle.breeding_fullness
+    }
+
+    /// Applies the cost of breeding to one parent and returns the fullness
+    /// that it passes on to the offspring.
+    pub fn breed(&mut self, life_cycle: &LifeCycle, state: &mut FaunaState) -> f32 {
+        state.fullness -= life_cycle.breeding_cost;
+        self.breeding_cooldown = life_cycle.breeding_interval;
+        life_cycle.breeding_cost
+    }
+}
+
+/// A fauna that is ready to breed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Breeder<Id, S> {
+    pub id: Id,
+    pub species: S,
+    pub position: Vec2,
+    pub mate_radius: f32,
+    pub mate_search_radius: f32,
+}
+
+/// Pairs up ready breeders of the same species that are within each other's
+/// mate radius.
+///
+/// Each breeder is paired at most once, in order. `populations` holds the
+/// current population of each species and is incremented for every pair, and
+/// no pairs are made for species that have reached `cap`.
+pub fn pair_mates<Id: Copy, S: Clone + Eq + Hash>(
+    breeders: &[Breeder<Id, S>],
+    populations: &mut HashMap<S, usize>,
+    cap: usize,
+) -> Vec<(Id, Id)> {
+    let mut paired = vec![false; breeders.len()];
+    let mut pairs = Vec::new();
+
+    for (index, breeder) in breeders.iter().enumerate() {
+        if paired[index] {
+            continue;
+        }
+
+        let population = populations.entry(breeder.species.clone()).or_default();
+        if *population >= cap {
+            continue;
+        }
+
+        let mate = breeders
+            .iter()
+            .enumerate()
+            .skip(index + 1)
+            .find(|(other_index, other)| {
+                let radius = breeder.mate_radius.min(other.mate_radius);
+                !paired[*other_index]
+                    && other.species == breeder.species
+                    && other.position.distance(breeder.position) <= radius
+            });
+
+        if let Some((mate_index, mate)) = mate {
+            paired[index] = true;
+            paired[mate_index] = true;
+            *population += 1;
+            pairs.push((breeder.id, mate.id));
+        }
+    }
+
+    pairs
+}
+
+/// Finds the index of the nearest other breeder of the same species within
+/// each breeder's mate search radius.
+pub fn nearest_mates<Id, S: PartialEq>(breeders: &[Breeder<Id, S>]) -> Vec<Option<usize>> {
+    breeders
+        .iter()
+        .enumerate()
+        .map(|(index, breeder)| {
+            let mut nearest = None;
+            let mut nearest_distance = breeder.mate_search_radius;
+            for (other_index, other) in breeders.iter().enumerate() {
+                if other_index == index || other.species != breeder.species {
+                    continue;
+                }
+
+                let distance = other.position.distance(breeder.position);
+                if distance < nearest_distance {
+                    nearest = Some(other_index);
+                    nearest_distance = distance;
+                }
+            }
+
+            nearest
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIFE_CYCLE: LifeCycle = LifeCycle {
+        maturity_age: 10.0,
+        max_age: 100.0,
+        breeding_fullness: 4.0,
+        breeding_cost: 1.0,
+        breeding_interval: 20.0,
+        mate_radius: 1.0,
+        mate_search_radius: 5.0,
+    };
+
+    fn breeder(id: u32, species: &'static str, x: f32) -> Breeder<u32, &'static str> {
+        Breeder {
+            id,
+            species,
+            position: Vec2::new(x, 0.0),
+            mate_radius: 2.0,
+            mate_search_radius: 5.0,
+        }
+    }
+
+    #[test]
+    fn only_fed_adults_off_cooldown_breed() {
+        let fed = FaunaState {
+            stamina: 0.0,
+            fullness: 5.0,
+        };
+
+        let mut lifetime = Lifetime {
+            age: 0.0,
+            breeding_cooldown: 0.0,
+        };
+
+        assert!(!lifetime.can_breed(&LIFE_CYCLE, &fed));
+
+        lifetime.grow_older(10.0);
+        assert!(lifetime.is_mature(&LIFE_CYCLE));
+        assert!(lifetime.can_breed(&LIFE_CYCLE, &fed));
+
+        let hungry = FaunaState {
+            fullness: 3.0,
+            ..fed
+        };
+        assert!(!lifetime.can_breed(&LIFE_CYCLE, &hungry));
+
+        lifetime.breeding_cooldown = 5.0;
+        assert!(!lifetime.can_breed(&LIFE_CYCLE, &fed));
+        lifetime.grow_older(5.0);
+        assert_eq!(lifetime.breeding_cooldown, 0.0);
+        assert!(lifetime.can_breed(&LIFE_CYCLE, &fed));
+
+        assert!(!lifetime.is_expired(&LIFE_CYCLE));
+        lifetime.grow_older(100.0);
+        assert!(lifetime.is_expired(&LIFE_CYCLE));
+    }
+
+    #[test]
+    fn mates_pair_within_species_and_radius() {
+        let breeders = [
+            breeder(0, "bunny", 0.0),
+            breeder(1, "fox", 1.0),
+            breeder(2, "bunny", 10.0),
+            breeder(3, "bunny", 1.5),
+            breeder(4, "bunny", 11.0),
+            breeder(5, "bunny", 0.5),
+        ];
+
+        let mut populations = HashMap::new();
+        let pairs = pair_mates(&breeders, &mut populations, 100);
+        assert_eq!(pairs, vec![(0, 3), (2, 4)]);
+        assert_eq!(populations["bunny"], 2);
+    }
+
+    #[test]
+    fn breeders_find_their_nearest_mates() {
+        let breeders = [
+            breeder(0, "bunny", 0.0),
+            breeder(1, "fox", 1.0),
+            breeder(2, "bunny", 4.0),
+            breeder(3, "bunny", 3.0),
+            breeder(4, "fox", 20.0),
+        ];
+
+        let mates = nearest_mates(&breeders);
+        assert_eq!(mates, vec![Some(3), None, Some(3), Some(2), None]);
+    }
+
+    #[test]
+    fn population_cap_limits_pairs() {
+        let breeders = [
+            breeder(0, "bunny", 0.0),
+            breeder(1, "bunny", 0.0),
+            breeder(2, "bunny", 0.0),
+            breeder(3, "bunny", 0.0),
+        ];
+
+        let mut populations = HashMap::from([("bunny", 4)]);
+        let pairs = pair_mates(&breeders, &mut populations, 5);
+        assert_eq!(pairs, vec![(0, 1)]);
+        assert_eq!(populations["bunny"], 5);
+    }
+
+    #[test]
+    fn breeding_transfers_fullness_to_offspring() {
+        let mut state = FaunaState {
+            stamina: 0.0,
+            fullness: 5.0,
+        };
+
+        let mut lifetime = Lifetime {
+            age: 50.0,
+            breeding_cooldown: 0.0,
+        };
+
+        let offspring_fullness = lifetime.breed(&LIFE_CYCLE, &mut state);
+        assert_eq!(offspring_fullness, 1.0);
+        assert_eq!(state.fullness, 4.0);
+        assert_eq!(lifetime.breeding_cooldown, 20.0);
+        assert!(!lifetime.can_breed(&LIFE_CYCLE, &state));
+    }
+}
@@ -0,0 +1,1246 @@
+//! A headless, deterministic simulation of the farm's ecosystem.
+//!
+//! The simulation runs the same map, crop, and fauna rules as the server at a
+//! fixed timestep, so that a given seed and configuration always produce the
+//! same results.
+
+use std::collections::HashMap;
+
+use glam::{IVec2, Vec2};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use crate::{
+    content::{self, SpeciesDef},
+    crops::{grow_medium, pick_spread_target, CropClass, CROP_GRID_CELL_SIZE, PEST_CROP_CHOICES},
+    fauna::{
+        Behavior, Diet, FaunaAction, FaunaState, Instincts, Locomotion, Metabolism, Surroundings,
+    },
+    foraging::{pick_food, pick_meal, FoodHere, FoodNearby, Meal},
+    map::{position_to_tile, scatter_wild_crops, TileMap},
+    movement::{movement_position, movement_progress},
+    partitioning::{Footprint, GridIndex, QbvhIndex, SpatialIndex},
+    pathfinding::{find_path, next_waypoint, tile_distance, PathCache, PathRules},
+    pollination::{wear_off_pollination, Hive, HiveConfig, POLLINATION_DURATION},
+    population::{nearest_mates, pair_mates, Breeder, LifeCycle, Lifetime},
+    soil::{excretion, Soil},
+};
+
+/// The tunable parameters of a single fauna species.
+#[derive(Clone, Debug)]
+pub struct SpeciesConfig {
+    pub name: &'static str,
+
+    /// The number of adults spawned at the start of the simulation.
+    pub count: usize,
+
+    pub diet: Diet,
+
+    /// The radius of the map area that each individual takes up, which
+    /// searches measure to instead of its center.
+    pub footprint: f32,
+
+    /// Whether carnivores hunt this species.
+    pub is_prey: bool,
+
+    /// Whether wary fauna flee from this species.
+    pub is_threat: bool,
+
+    pub metabolism: Metabolism,
+    pub instincts: Instincts,
+    pub life_cycle: LifeCycle,
+
+    /// The range that each individual's movement cost is randomly picked from.
+    pub movement_cost: (f32, f32),
+    pub movement_distance: f32,
+
+    /// The radius in which this species looks for food.
+    pub search_radius: f32,
+
+    /// The radius in which an herbivore looks for medium crops to eat.
+    /// Species with a radius of zero leave medium crops alone.
+    pub crop_search_radius: f32,
+
+    /// The radius in which this species notices and flees from threats.
+    /// Species with a wariness of zero never flee.
+    pub wariness: f32,
+
+    /// The rules that this species follows when pathing to food and mates.
+    /// Species without rules move straight toward them, like flying
+    /// pollinators.
+    pub path_rules: Option<PathRules>,
+
+    /// The fullness of the adults spawned at the start of the simulation.
+    pub fullness: f32,
+
+    /// The fullness that a carnivore gains by eating this species.
+    pub sustenance: f32,
+
+    /// The nutrients that this species returns to the soil when it starves
+    /// or dies of old age.
+    pub remains: f32,
+}
+
+impl SpeciesConfig {
+    /// The parameters of a species defined in the game's content, spawning
+    /// `count` adults at the start of the simulation.
+    pub fn from_def(def: &SpeciesDef, count: usize) -> Self {
+        Self {
+            name: def.id,
+            count,
+            diet: def.diet,
+            footprint: def.footprint,
+            is_prey: def.is_prey,
+            is_threat: def.is_threat,
+            metabolism: def.metabolism,
+            instincts: def.instincts,
+            life_cycle: def.life_cycle,
+            movement_cost: def.movement_cost,
+            movement_distance: def.movement_distance,
+            search_radius: def.search_radius,
+            crop_search_radius: def.crop_search_radius,
+            wariness: def.wariness,
+            path_rules: def.path_rules,
+            fullness: def.fullness,
+            sustenance: def.sustenance,
+            remains: def.remains,
+        }
+    }
+}
+
+/// The tunable parameters of a simulation.
+///
+/// The defaults match the values used by the server.
+#[derive(Clone, Debug)]
+pub struct SimConfig {
+    pub seed: u64,
+    pub map_size: IVec2,
+
+    /// The length of a single tick in seconds.
+    pub tick_duration: f32,
+
+    /// The time in seconds between crop growth and spreading ticks.
+    pub grow_interval: f32,
+
+    /// The fullness restored by eating a cover crop.
+    pub sustenance: f32,
+
+    pub species: Vec<SpeciesConfig>,
+
+    /// The maximum population of each fauna species.
+    pub population_cap: usize,
+
+    /// The time in seconds that a single hop takes.
+    pub hop_duration: f32,
+
+    /// Whether wild crops are scattered across the map when it's created.
+    pub wild_crops: bool,
+
+    /// The tiles that pollinator hives are placed on.
+    pub hives: Vec<IVec2>,
+
+    pub hive: HiveConfig,
+}
+
+impl SimConfig {
+    /// Looks up the parameters of a species by name.
+    pub fn species_mut(&mut self, name: &str) -> Option<&mut SpeciesConfig> {
+        self.species.iter_mut().find(|species| species.name == name)
+    }
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        let map_size = IVec2::new(32, 32);
+        let tiles = (map_size.x * map_size.y) as usize;
+
+        Self {
+            seed: 0,
+            map_size,
+            tick_duration: 1.0 / 60.0,
+            grow_interval: 1.0,
+            sustenance: 2.0,
+            species: content::SPECIES
+                .iter()
+                .map(|def| {
+                    // populate the map the way the server's spawners do
+                    let count = content::SPAWNERS
+                        .iter()
+                        .filter(|spawner| spawner.species == def.id)
+                        .map(|spawner| spawner.spawn_count(tiles))
+                        .sum();
+
+                    SpeciesConfig::from_def(def, count)
+                })
+                .collect(),
+            population_cap: 40,
+            hop_duration: 0.25,
+            wild_crops: true,
+            hives: Vec::new(),
+            hive: HiveConfig {
+                spawn_interval: 10.0,
+                capacity: 5,
+            },
+        }
+    }
+}
+
+/// The contents of a single simulated tile.
+#[derive(Clone, Debug, Default)]
+struct SimTile {
+    cover_crop: bool,
+    medium_crop: Option<&'static str>,
+
+    /// The time in seconds that this tile's medium crop stays pollinated.
+    pollination: f32,
+
+    soil: Soil,
+}
+
+/// A hop in progress.
+#[derive(Clone, Copy, Debug)]
+struct Hop {
+    step: f32,
+    start: Vec2,
+    target: Vec2,
+}
+
+/// What an animal knows about its surroundings.
+struct Senses {
+    here: FoodHere<IVec2, usize, &'static str>,
+    nearby: FoodNearby<Vec2, &'static str>,
+
+    /// The position of the nearest threat.
+    threat: Option<Vec2>,
+}
+
+/// A single simulated fauna.
+#[derive(Clone, Debug)]
+struct Animal {
+    /// The index of this animal's species in [SimConfig::species].
+    species: usize,
+    position: Vec2,
+    state: FaunaState,
+    behavior: Behavior,
+    lifetime: Lifetime,
+    locomotion: Locomotion,
+    hop: Option<Hop>,
+
+    /// The position of this animal's nearest mate, if it's ready to breed.
+    mate: Option<Vec2>,
+
+    /// Whether this animal was eaten during the current tick.
+    eaten: bool,
+
+    /// The index of the hive that spawned this animal, if any.
+    home: Option<usize>,
+}
+
+/// A single simulated pollinator hive.
+#[derive(Clone, Copy, Debug)]
+struct SimHive {
+    tile: IVec2,
+    hive: Hive,
+}
+
+/// Population counts at a single point in time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Census {
+    pub tick: u64,
+    pub time: f32,
+
+    /// The number of tiles covered by cover crops.
+    pub cover_crops: usize,
+
+    /// The number of medium crops of each class, in [content::CROPS] order.
+    pub medium_crops: Vec<(&'static str, usize)>,
+
+    /// The population of each fauna species, in [SimConfig::species] order.
+    pub fauna: Vec<(&'static str, usize)>,
+
+    /// The total nutrients in the soil of every tile.
+    pub nutrients: f32,
+}
+
+impl Census {
+    /// The population of the species with the given name.
+    pub fn population(&self, species: &str) -> usize {
+        self.fauna
+            .iter()
+            .find(|(name, _)| *name == species)
+            .map_or(0, |(_, count)| *count)
+    }
+}
+
+/// The state of a running simulation.
+pub struct Simulation {
+    config: SimConfig,
+    rng: StdRng,
+    tick: u64,
+    grow_timer: f32,
+    map: TileMap<SimTile>,
+    crop_classes: HashMap<&'static str, CropClass<&'static str>>,
+    animals: Vec<Animal>,
+    hives: Vec<SimHive>,
+
+    /// Paths keyed by the index of the species that follows them.
+    paths: PathCache<usize, IVec2>,
+
+    /// The tiles of cover crops, of medium crops that tempt pests, and of
+    /// flowering crops, indexed like the server indexes crops.
+    cover_crops: GridIndex<IVec2>,
+    pest_crops: GridIndex<IVec2>,
+    flowers: GridIndex<IVec2>,
+}
+
+impl Simulation {
+    /// Creates a simulation of the built-in world content.
+    pub fn new(config: SimConfig) -> Self {
+        let mut rng = StdRng::seed_from_u64(config.seed);
+
+        let crop_classes = content::CROPS
+            .iter()
+            .map(|def| {
+                let class = CropClass {
+                    seed: def.seed,
+                    next_growth_phase: def.next_growth_phase,
+                    damaged_phase: def.damaged_phase,
+                    pest_attractiveness: def.pest_attractiveness,
+                    sustenance: def.sustenance,
+                    nectar: def.nectar,
+                };
+
+                (def.id, class)
+            })
+            .collect();
+
+        let mut map = TileMap::new(config.map_size, |_| SimTile {
+            cover_crop: true,
+            ..Default::default()
+        });
+
+        for def in content::WORLD_CROPS {
+            if let Some(tile) = map.get_mut(IVec2::from_array(def.tile)) {
+                tile.medium_crop = Some(def.crop);
+            }
+        }
+
+        let taken = content::placed_tiles();
+        let wild_crops = if config.wild_crops {
+            content::WILD_CROPS
+        } else {
+            &[]
+        };
+
+        let wild = scatter_wild_crops(wild_crops, config.map_size, &taken, &mut rng);
+        for (xy, crop) in wild {
+            if let Some(tile) = map.get_mut(xy) {
+                tile.medium_crop = Some(crop);
+            }
+        }
+
+        let mut tiles: Vec<IVec2> = map.iter().map(|(xy, _)| xy).collect();
+        let mut animals = Vec::new();
+        for (index, species) in config.species.iter().enumerate() {
+            let (spawn_tiles, _) = tiles.partial_shuffle(&mut rng, species.count);
+            for xy in spawn_tiles.iter() {
+                let age = species.life_cycle.maturity_age;
+                let animal = new_animal(
+                    &config,
+                    &mut rng,
+                    index,
+                    xy.as_vec2(),
+                    species.fullness,
+                    age,
+                );
+                animals.push(animal);
+            }
+        }
+
+        let hives = config
+            .hives
+            .iter()
+            .map(|tile| SimHive {
+                tile: *tile,
+                hive: Hive::default(),
+            })
+            .collect();
+
+        let mut sim = Self {
+            config,
+            rng,
+            tick: 0,
+            grow_timer: 0.0,
+            map,
+            crop_classes,
+            animals,
+            hives,
+            paths: PathCache::new(4096),
+            cover_crops: GridIndex::new(CROP_GRID_CELL_SIZE),
+            pest_crops: GridIndex::new(CROP_GRID_CELL_SIZE),
+            flowers: GridIndex::new(CROP_GRID_CELL_SIZE),
+        };
+
+        let crops: Vec<(IVec2, bool, Option<&'static str>)> = sim
+            .map
+            .iter()
+            .map(|(xy, tile)| (xy, tile.cover_crop, tile.medium_crop))
+            .collect();
+
+        for (xy, cover_crop, medium_crop) in crops {
+            sim.set_cover_crop(xy, cover_crop);
+            sim.set_medium_crop(xy, medium_crop);
+        }
+
+        sim
+    }
+
+    /// Advances the simulation by a single tick.
+    pub fn step(&mut self) {
+        let dt = self.config.tick_duration;
+        self.tick += 1;
+
+        self.step_hops(dt);
+        self.step_metabolism(dt);
+        self.step_aging(dt);
+        self.step_breeding();
+        self.step_hives(dt);
+        self.step_fauna();
+        self.step_pollination(dt);
+
+        self.grow_timer += dt;
+        while self.grow_timer >= self.config.grow_interval {
+            self.grow_timer -= self.config.grow_interval;
+            self.grow_cover_crops();
+            self.grow_medium_crops();
+            self.paths.clear();
+        }
+    }
+
+    /// Counts the current populations.
+    pub fn census(&self) -> Census {
+        let mut medium_counts: HashMap<&str, usize> = HashMap::new();
+        let mut cover_crops = 0;
+        let mut nutrients = 0.0;
+        for (_xy, tile) in self.map.iter() {
+            nutrients += tile.soil.nutrients;
+            if tile.cover_crop {
+                cover_crops += 1;
+            }
+
+            if let Some(crop) = tile.medium_crop {
+                *medium_counts.entry(crop).or_default() += 1;
+            }
+        }
+
+        let medium_crops = content::CROPS
+            .iter()
+            .map(|def| (def.id, medium_counts.get(def.id).copied().unwrap_or(0)))
+            .collect();
+
+        let fauna = self
+            .config
+            .species
+            .iter()
+            .enumerate()
+            .map(|(index, species)| (species.name, self.population(index)))
+            .collect();
+
+        Census {
+            tick: self.tick,
+            time: self.tick as f32 * self.config.tick_duration,
+            cover_crops,
+            medium_crops,
+            fauna,
+            nutrients,
+        }
+    }
+
+    /// Helper function to count the population of a species.
+    fn population(&self, species: usize) -> usize {
+        self.animals
+            .iter()
+            .filter(|animal| animal.species == species)
+            .count()
+    }
+
+    /// Helper function to advance hops in progress.
+    fn step_hops(&mut self, dt: f32) {
+        let duration = self.config.hop_duration;
+        for animal in self.animals.iter_mut() {
+            let Some(hop) = animal.hop.as_mut() else {
+                continue;
+            };
+
+            hop.step += dt;
+            if hop.step > duration {
+                animal.position = hop.target;
+                animal.hop = None;
+            } else {
+                let progress = movement_progress(hop.step, duration);
+                animal.position = movement_position(hop.start, hop.target, progress);
+            }
+        }
+    }
+
+    /// Helper function to advance metabolism and remove starved animals.
+    fn step_metabolism(&mut self, dt: f32) {
+        for animal in self.animals.iter_mut() {
+            let metabolism = self.config.species[animal.species].metabolism;
+            animal.state.metabolize(&metabolism, dt);
+        }
+
+        self.remove_dead(|animal, _| animal.state.is_starved());
+    }
+
+    /// Helper function to age animals and remove those that die of old age.
+    fn step_aging(&mut self, dt: f32) {
+        for animal in self.animals.iter_mut() {
+            animal.lifetime.grow_older(dt);
+        }
+
+        self.remove_dead(|animal, species| animal.lifetime.is_expired(&species.life_cycle));
+    }
+
+    /// Helper function to remove the animals that have died, returning their
+    /// remains to the soil that they died on.
+    fn remove_dead(&mut self, is_dead: impl Fn(&Animal, &SpeciesConfig) -> bool) {
+        let species = &self.config.species;
+        let map = &mut self.map;
+        self.animals.retain(|animal| {
+            let species = &species[animal.species];
+            if !is_dead(animal, species) {
+                return true;
+            }
+
+            if let Some(tile) = map.get_mut(position_to_tile(animal.position)) {
+                tile.soil.fertilize(species.remains);
+            }
+
+            false
+        });
+    }
+
+    /// Helper function to pair up ready animals, spawn their offspring, and
+    /// point the remaining ready animals toward their nearest mates.
+    fn step_breeding(&mut self) {
+        let breeders = self.ready_breeders();
+        let mut populations = (0..self.config.species.len())
+            .map(|species| (species, self.population(species)))
+            .collect();
+
+        let cap = self.config.population_cap;
+        for (a, b) in pair_mates(&breeders, &mut populations, cap) {
+            let species = self.animals[a].species;
+            let life_cycle = self.config.species[species].life_cycle;
+
+            let mut fullness = 0.0;
+            for parent in [a, b] {
+                let parent = &mut self.animals[parent];
+                fullness += parent.lifetime.breed(&life_cycle, &mut parent.state);
+            }
+
+            let position = self.animals[a].position.lerp(self.animals[b].position, 0.5);
+            let offspring = new_animal(
+                &self.config,
+                &mut self.rng,
+                species,
+                position,
+                fullness,
+                0.0,
+            );
+            self.animals.push(offspring);
+        }
+
+        for animal in self.animals.iter_mut() {
+            animal.mate = None;
+        }
+
+        let mut breeders = self.ready_breeders();
+        breeders.retain(|breeder| populations[&breeder.species] < cap);
+        for (breeder, mate) in breeders.iter().zip(nearest_mates(&breeders)) {
+            let mate = mate.map(|mate| breeders[mate].position);
+            self.animals[breeder.id].mate = mate;
+        }
+    }
+
+    /// Helper function to spawn pollinators from hives.
+    fn step_hives(&mut self, dt: f32) {
+        let pollinator = self
+            .config
+            .species
+            .iter()
+            .position(|species| species.diet == Diet::Pollinator);
+
+        let Some(pollinator) = pollinator else {
+            return;
+        };
+
+        for index in 0..self.hives.len() {
+            let population = self
+                .animals
+                .iter()
+                .filter(|animal| animal.home == Some(index))
+                .count();
+
+            let hive = &mut self.hives[index];
+            if !hive.hive.tick(&self.config.hive, dt, population) {
+                continue;
+            }
+
+            let position = hive.tile.as_vec2();
+            let fullness = self.config.species[pollinator].fullness;
+            let mut bee = new_animal(
+                &self.config,
+                &mut self.rng,
+                pollinator,
+                position,
+                fullness,
+                0.0,
+            );
+            bee.home = Some(index);
+            self.animals.push(bee);
+        }
+    }
+
+    /// Helper function to wear off the pollination of medium crops.
+    fn step_pollination(&mut self, dt: f32) {
+        let tiles: Vec<IVec2> = self.map.iter().map(|(xy, _)| xy).collect();
+        for xy in tiles {
+            if let Some(tile) = self.map.get_mut(xy) {
+                tile.pollination = wear_off_pollination(tile.pollination, dt).unwrap_or(0.0);
+            }
+        }
+    }
+
+    /// Helper function to list the animals that are ready to breed.
+    fn ready_breeders(&self) -> Vec<Breeder<usize, usize>> {
+        self.animals
+            .iter()
+            .enumerate()
+            .filter_map(|(index, animal)| {
+                let life_cycle = &self.config.species[animal.species].life_cycle;
+                if !animal.lifetime.can_breed(life_cycle, &animal.state) {
+                    return None;
+                }
+
+                Some(Breeder {
+                    id: index,
+                    species: animal.species,
+                    position: animal.position,
+                    mate_radius: life_cycle.mate_radius,
+                    mate_search_radius: life_cycle.mate_search_radius,
+                })
+            })
+            .collect()
+    }
+
+    /// Helper function to let every resting animal act.
+    fn step_fauna(&mut self) {
+        let mut fauna = self.index_fauna();
+        for index in 0..self.animals.len() {
+            let animal = &self.animals[index];
+            if animal.eaten || animal.hop.is_some() {
+                continue;
+            }
+
+            let position = animal.position;
+            let tile = position_to_tile(position);
+            let species = animal.species;
+            let mate = animal.mate;
+            let diet = self.config.species[species].diet;
+            let is_pest = self.config.species[species].crop_search_radius > 0.0;
+
+            let senses = self.sense(&fauna, index);
+            let meal = pick_meal(diet, is_pest, senses.here, &mut self.rng);
+            let food = pick_food(diet, senses.nearby, &mut self.rng);
+            let food = food.and_then(|food| self.waypoint(species, position, food));
+            let mate = mate.and_then(|mate| self.waypoint(species, position, mate));
+
+            let surroundings = Surroundings {
+                food_here: meal.map(|(_, sustenance)| sustenance),
+                food,
+                threat: senses.threat,
+                mate,
+                owner: None,
+                bounds: (Vec2::ZERO, (self.config.map_size - 1).as_vec2()),
+            };
+
+            let instincts = self.config.species[species].instincts;
+            let animal = &mut self.animals[index];
+            let (behavior, action) = animal.state.update(
+                animal.behavior,
+                &animal.locomotion,
+                &instincts,
+                position,
+                &surroundings,
+                &mut self.rng,
+            );
+
+            animal.behavior = behavior;
+            match action {
+                FaunaAction::Eat => {
+                    let Some((meal, sustenance)) = meal else {
+                        continue;
+                    };
+
+                    // eaters return some of what they eat to the soil
+                    if let Some(tile) = self.map.get_mut(tile) {
+                        tile.soil.fertilize(excretion(sustenance));
+                    }
+
+                    match meal {
+                        Meal::Prey(prey) => {
+                            self.animals[prey].eaten = true;
+                            fauna.remove(prey);
+                        }
+                        Meal::MediumCrop(xy) => self.damage_medium_crop(xy),
+                        Meal::Flower(xy) => {
+                            if let Some(tile) = self.map.get_mut(xy) {
+                                tile.pollination = POLLINATION_DURATION;
+                            }
+                        }
+                        Meal::CoverCrop(xy) => self.set_cover_crop(xy, false),
+                    }
+                }
+                FaunaAction::Hop(delta) => {
+                    animal.hop = Some(Hop {
+                        step: 0.0,
+                        start: position,
+                        target: position + delta,
+                    });
+                }
+                FaunaAction::Rest | FaunaAction::Idle => {}
+            }
+        }
+
+        self.animals.retain(|animal| !animal.eaten);
+    }
+
+    /// Helper function to gather the food on an animal's tile, the food
+    /// around it, and the nearest threat.
+    ///
+    /// Animals only look around if they're about to make a decision, and
+    /// search the same indices that the server's fauna search.
+    fn sense(&self, fauna: &QbvhIndex<usize>, index: usize) -> Senses {
+        let animal = &self.animals[index];
+        let species = &self.config.species[animal.species];
+        let position = animal.position;
+        let tile = position_to_tile(position);
+        let will_act = animal.state.stamina >= animal.locomotion.movement_cost;
+
+        let search = |radius: f32, filter: &dyn Fn(&SpeciesConfig) -> bool| {
+            fauna
+                .nearest(position, radius, |other| {
+                    other != index && filter(&self.config.species[self.animals[other].species])
+                })
+                .map(|hit| hit.id)
+        };
+
+        let hunts = will_act && species.diet == Diet::Carnivore;
+        let prey = hunts
+            .then(|| search(species.search_radius, &|other| other.is_prey))
+            .flatten();
+
+        let here = FoodHere {
+            cover_crop: self
+                .map
+                .get(tile)
+                .filter(|tile| tile.cover_crop)
+                .map(|_| (tile, self.config.sustenance)),
+            medium_crop: self
+                .map
+                .get(tile)
+                .and_then(|tile| tile.medium_crop)
+                .and_then(|crop| self.crop_classes.get(crop))
+                .map(|class| (tile, *class)),
+            prey: prey
+                .filter(|prey| position_to_tile(self.animals[*prey].position) == tile)
+                .map(|prey| {
+                    let sustenance = self.config.species[self.animals[prey].species].sustenance;
+                    (prey, sustenance)
+                }),
+        };
+
+        if !will_act {
+            return Senses {
+                here,
+                nearby: FoodNearby::default(),
+                threat: None,
+            };
+        }
+
+        let tiles = |grid: &GridIndex<IVec2>, radius: f32, k: usize| {
+            grid.nearest_k(position, radius, k, |_| true)
+                .into_iter()
+                .map(|hit| hit.id)
+        };
+
+        let nearby = match species.diet {
+            Diet::Herbivore => FoodNearby {
+                medium_crops: tiles(
+                    &self.pest_crops,
+                    species.crop_search_radius,
+                    PEST_CROP_CHOICES,
+                )
+                .filter_map(|xy| {
+                    let crop = self.map.get(xy)?.medium_crop?;
+                    Some((xy.as_vec2(), *self.crop_classes.get(crop)?))
+                })
+                .collect(),
+                cover_crop: tiles(&self.cover_crops, species.search_radius, 1)
+                    .next()
+                    .map(|xy| xy.as_vec2()),
+                ..Default::default()
+            },
+            Diet::Pollinator => FoodNearby {
+                flower: tiles(&self.flowers, species.search_radius, 1)
+                    .next()
+                    .map(|xy| xy.as_vec2()),
+                ..Default::default()
+            },
+            Diet::Carnivore => FoodNearby {
+                prey: prey.map(|prey| self.animals[prey].position),
+                ..Default::default()
+            },
+        };
+
+        let threat = search(species.wariness, &|other| other.is_threat)
+            .map(|threat| self.animals[threat].position);
+
+        Senses {
+            here,
+            nearby,
+            threat,
+        }
+    }
+
+    /// Helper function to index every living animal by the area that it
+    /// takes up, like the server indexes fauna.
+    fn index_fauna(&self) -> QbvhIndex<usize> {
+        let mut index = QbvhIndex::new();
+        for (id, animal) in self.animals.iter().enumerate() {
+            let footprint = Footprint::Circle(self.config.species[animal.species].footprint);
+            index.insert_with_footprint(id, animal.position, footprint);
+        }
+
+        // flushed indices break ties between equally near animals the same way
+        // every run
+        index.flush();
+        index
+    }
+
+    /// Helper function to find where an animal should head next on its way
+    /// to a target, or `None` if it can't reach the target.
+    fn waypoint(&mut self, species: usize, position: Vec2, target: Vec2) -> Option<Vec2> {
+        let Some(rules) = self.config.species[species].path_rules else {
+            return Some(target);
+        };
+
+        let start = position_to_tile(position);
+        let goal = position_to_tile(target);
+        if start == goal {
+            return Some(target);
+        }
+
+        let map = &self.map;
+        let path = self.paths.get_or_find(species, start, goal, || {
+            find_path(
+                start,
+                goal,
+                |xy, dir| map.neighbor(xy, dir),
+                |xy| rules.step_cost(map.get(xy)?.medium_crop.is_some()),
+                |xy| tile_distance(xy.as_vec2(), goal.as_vec2()),
+            )
+        })?;
+
+        next_waypoint(path).map(|xy| xy.as_vec2())
+    }
+
+    /// Helper function to regress or destroy a medium crop eaten by a pest.
+    fn damage_medium_crop(&mut self, xy: IVec2) {
+        let damaged_phase = self
+            .map
+            .get(xy)
+            .and_then(|tile| tile.medium_crop)
+            .and_then(|crop| self.crop_classes.get(crop))
+            .and_then(|class| class.damaged_phase);
+
+        self.set_medium_crop(xy, damaged_phase);
+        self.paths.clear();
+    }
+
+    /// Helper function to plant or clear a tile's cover crop, keeping the
+    /// cover crop index up to date.
+    fn set_cover_crop(&mut self, xy: IVec2, cover_crop: bool) {
+        let Some(tile) = self.map.get_mut(xy) else {
+            return;
+        };
+
+        tile.cover_crop = cover_crop;
+        if cover_crop {
+            self.cover_crops.insert(xy, xy.as_vec2());
+        } else {
+            self.cover_crops.remove(xy);
+        }
+    }
+
+    /// Helper function to plant, replace, or clear a tile's medium crop,
+    /// keeping the medium crop indices up to date.
+    fn set_medium_crop(&mut self, xy: IVec2, crop: Option<&'static str>) {
+        let Some(tile) = self.map.get_mut(xy) else {
+            return;
+        };
+
+        tile.medium_crop = crop;
+        let class = crop.and_then(|crop| self.crop_classes.get(crop));
+        let tempts_pests = class.is_some_and(|class| class.pest_attractiveness > 0.0);
+        let is_flowering = class.is_some_and(|class| class.is_flowering());
+        for (index, indexed) in [
+            (&mut self.pest_crops, tempts_pests),
+            (&mut self.flowers, is_flowering),
+        ] {
+            if indexed {
+                index.insert(xy, xy.as_vec2());
+            } else {
+                index.remove(xy);
+            }
+        }
+    }
+
+    /// Helper function to spread every cover crop by one grow tick.
+    fn grow_cover_crops(&mut self) {
+        let growable: Vec<IVec2> = self
+            .map
+            .iter()
+            .filter(|(_, tile)| tile.cover_crop)
+            .map(|(xy, _)| xy)
+            .collect();
+
+        for xy in growable {
+            let map = &self.map;
+            let target = pick_spread_target(
+                &mut self.rng,
+                |dir| map.neighbor(xy, dir),
+                |neighbor| {
+                    map.get(*neighbor)
+                        .is_some_and(|tile| !tile.cover_crop && tile.soil.can_grow_cover_crop())
+                },
+            );
+
+            let Some(neighbor) = target else {
+                continue;
+            };
+
+            let grown = self
+                .map
+                .get_mut(neighbor)
+                .is_some_and(|tile| tile.soil.grow_cover_crop());
+
+            if grown {
+                self.set_cover_crop(neighbor, true);
+            }
+        }
+    }
+
+    /// Helper function to grow every medium crop by one grow tick.
+    fn grow_medium_crops(&mut self) {
+        let growable: Vec<(IVec2, &'static str, bool)> = self
+            .map
+            .iter()
+            .filter_map(|(xy, tile)| Some((xy, tile.medium_crop?, tile.pollination > 0.0)))
+            .collect();
+
+        for (xy, crop, pollinated) in growable {
+            let Some(class) = self.crop_classes.get(crop) else {
+                continue;
+            };
+
+            let map = &self.map;
+            let growth = grow_medium(
+                &mut self.rng,
+                class,
+                pollinated,
+                |dir| map.neighbor(xy, dir),
+                |neighbor| {
+                    map.get(*neighbor)
+                        .is_some_and(|tile| tile.medium_crop.is_none())
+                },
+            );
+
+            for (neighbor, seed) in growth.spread {
+                self.set_medium_crop(neighbor, Some(seed));
+            }
+
+            if let Some(next_phase) = growth.next_phase {
+                self.set_medium_crop(xy, Some(next_phase));
+            }
+        }
+    }
+}
+
+/// Helper function to create an animal with randomized traits.
+fn new_animal(
+    config: &SimConfig,
+    rng: &mut impl Rng,
+    species: usize,
+    position: Vec2,
+    fullness: f32,
+    age: f32,
+) -> Animal {
+    let params = &config.species[species];
+    let (min_cost, max_cost) = params.movement_cost;
+    Animal {
+        species,
+        position,
+        state: FaunaState {
+            stamina: 0.0,
+            fullness,
+        },
+        behavior: Behavior::Idle,
+        lifetime: Lifetime {
+            age,
+            breeding_cooldown: 0.0,
+        },
+        locomotion: Locomotion {
+            movement_cost: rng.gen_range(min_cost..=max_cost),
+            movement_distance: params.movement_distance,
+        },
+        hop: None,
+        mate: None,
+        eaten: false,
+        home: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(config: SimConfig, ticks: u64) -> Vec<Census> {
+        let mut sim = Simulation::new(config);
+        (0..ticks)
+            .map(|_| {
+                sim.step();
+                sim.census()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_gives_same_results() {
+        let config = SimConfig {
+            seed: 42,
+            ..Default::default()
+        };
+
+        assert_eq!(run(config.clone(), 600), run(config, 600));
+    }
+
+    /// Helper function to get the default parameters of a species.
+    fn species(name: &str) -> SpeciesConfig {
+        SimConfig::default().species_mut(name).unwrap().clone()
+    }
+
+    fn bunnies_only(config: SimConfig) -> SimConfig {
+        SimConfig {
+            species: vec![species("bunny")],
+            ..config
+        }
+    }
+
+    #[test]
+    fn bunnies_graze_and_starve_without_food() {
+        let config = bunnies_only(SimConfig {
+            sustenance: 0.0,
+            ..Default::default()
+        });
+
+        let censuses = run(config, 60 * 12);
+        let first = censuses.first().unwrap();
+        let last = censuses.last().unwrap();
+        assert_eq!(first.population("bunny"), 5);
+        assert_eq!(last.population("bunny"), 0);
+        assert!(censuses.iter().any(|census| census.cover_crops < 32 * 32));
+    }
+
+    #[test]
+    fn bunnies_breed_up_to_the_population_cap() {
+        let config = bunnies_only(SimConfig {
+            population_cap: 12,
+            ..Default::default()
+        });
+
+        let censuses = run(config, 60 * 150);
+        let peak = censuses
+            .iter()
+            .map(|census| census.population("bunny"))
+            .max()
+            .unwrap();
+        assert_eq!(peak, 12);
+    }
+
+    #[test]
+    fn foxes_hunt_bunnies() {
+        let mut bunny = species("bunny");
+        bunny.count = 10;
+        bunny.life_cycle.breeding_fullness = f32::INFINITY;
+
+        let mut fox = species("fox");
+        fox.fullness = 1.0;
+
+        let config = SimConfig {
+            species: vec![bunny, fox],
+            ..Default::default()
+        };
+
+        let censuses = run(config, 60 * 60);
+        let last = censuses.last().unwrap();
+        assert!(last.population("bunny") < 10);
+        assert_eq!(last.population("fox"), 2);
+    }
+
+    #[test]
+    fn bunnies_flee_from_foxes() {
+        let mut bunny = species("bunny");
+        bunny.count = 1;
+
+        let mut fox = species("fox");
+        fox.count = 1;
+        fox.search_radius = 0.0;
+        fox.instincts.wander_chance = 0.0;
+
+        let mut sim = Simulation::new(SimConfig {
+            species: vec![bunny, fox],
+            ..Default::default()
+        });
+
+        sim.animals[0].position = Vec2::new(10.0, 10.0);
+        sim.animals[1].position = Vec2::new(11.0, 10.0);
+
+        let mut fled = false;
+        for _ in 0..60 {
+            sim.step();
+            fled |= sim.animals[0].behavior == Behavior::Flee;
+        }
+
+        assert!(fled);
+        assert!(sim.animals[0].position.x < 10.0);
+    }
+
+    #[test]
+    fn pests_eat_medium_crops() {
+        let mut bunny = species("bunny");
+        bunny.count = 1;
+
+        let mut sim = Simulation::new(SimConfig {
+            grow_interval: f32::INFINITY,
+            species: vec![bunny],
+            ..Default::default()
+        });
+
+        let xy = IVec2::new(10, 10);
+        let tiles: Vec<IVec2> = sim.map.iter().map(|(xy, _)| xy).collect();
+        for tile in tiles {
+            sim.set_cover_crop(tile, false);
+        }
+
+        sim.set_medium_crop(xy, Some("maize"));
+        sim.animals[0].position = xy.as_vec2();
+
+        let mut eaten = Vec::new();
+        for _ in 0..60 * 10 {
+            sim.step();
+            let crop = sim.map.get(xy).unwrap().medium_crop;
+            if eaten.last() != Some(&crop) {
+                eaten.push(crop);
+            }
+        }
+
+        assert_eq!(eaten[..2], [Some("maize"), Some("maize_stage_3")]);
+    }
+
+    #[test]
+    fn hives_spawn_pollinators() {
+        let mut sim = Simulation::new(SimConfig {
+            species: vec![species("bee")],
+            hives: vec![IVec2::new(10, 10)],
+            ..Default::default()
+        });
+
+        let flower = IVec2::new(13, 10);
+        sim.set_medium_crop(flower, Some("iris"));
+
+        let mut pollinated = false;
+        for _ in 0..60 * 60 {
+            sim.step();
+            pollinated |= sim.map.get(flower).unwrap().pollination > 0.0;
+        }
+
+        assert!(pollinated);
+        assert_eq!(sim.census().population("bee"), 5);
+    }
+
+    #[test]
+    fn cover_crops_need_nutrients_to_spread() {
+        let mut sim = Simulation::new(SimConfig {
+            species: Vec::new(),
+            ..Default::default()
+        });
+
+        // only the first two columns have enough nutrients for one cover crop
+        let tiles: Vec<IVec2> = sim.map.iter().map(|(xy, _)| xy).collect();
+        for xy in tiles {
+            sim.set_cover_crop(xy, xy == IVec2::ZERO);
+            let tile = sim.map.get_mut(xy).unwrap();
+            tile.soil.nutrients = if xy.x < 2 { 1.0 } else { 0.0 };
+        }
+
+        for _ in 0..60 * 60 {
+            sim.step();
+        }
+
+        let census = sim.census();
+        assert_eq!(census.cover_crops, 64);
+        assert_eq!(census.nutrients, 1.0);
+    }
+
+    #[test]
+    fn medium_crops_grow_and_spread() {
+        let config = SimConfig {
+            species: Vec::new(),
+            wild_crops: false,
+            ..Default::default()
+        };
+
+        let last = run(config, 60 * 10).pop().unwrap();
+        let count = |id| {
+            last.medium_crops
+                .iter()
+                .find(|(crop, _)| *crop == id)
+                .unwrap()
+                .1
+        };
+
+        assert_eq!(count("iris"), 0);
+        assert!(count("maize") > 1);
+        assert!(count("maize_stage_1") > 0);
+    }
+
+    #[test]
+    fn wild_crops_are_scattered() {
+        let config = SimConfig {
+            species: Vec::new(),
+            ..Default::default()
+        };
+
+        let first = run(config, 1).pop().unwrap();
+        let irises = first
+            .medium_crops
+            .iter()
+            .find(|(crop, _)| *crop == "iris")
+            .unwrap()
+            .1;
+
+        assert_eq!(irises, 8);
+    }
+}
@@ -0,0 +1,83 @@
+//! Rules for soil nutrients, which cover crops take up and fauna give back.
+
+/// The nutrients that each tile starts with.
+pub const INITIAL_NUTRIENTS: f32 = 5.0;
+
+/// The most nutrients that a single tile can hold.
+pub const MAX_NUTRIENTS: f32 = 10.0;
+
+/// The nutrients that a cover crop takes up from a tile that it spreads onto.
+pub const COVER_CROP_NUTRIENTS: f32 = 1.0;
+
+/// The fraction of the sustenance that fauna eat that they excrete back into
+/// the soil.
+pub const EXCRETED_FRACTION: f32 = 0.25;
+
+/// The nutrients in a single tile's soil.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Soil {
+    pub nutrients: f32,
+}
+
+impl Default for Soil {
+    fn default() -> Self {
+        Self {
+            nutrients: INITIAL_NUTRIENTS,
+        }
+    }
+}
+
+impl Soil {
+    /// Whether this soil has the nutrients to support a new cover crop.
+    pub fn can_grow_cover_crop(&self) -> bool {
+        self.nutrients >= COVER_CROP_NUTRIENTS
+    }
+
+    /// Takes up the nutrients of a new cover crop, returning whether there
+    /// were enough of them.
+    pub fn grow_cover_crop(&mut self) -> bool {
+        if !self.can_grow_cover_crop() {
+            return false;
+        }
+
+        self.nutrients -= COVER_CROP_NUTRIENTS;
+        true
+    }
+
+    /// Adds nutrients to this soil, up to [MAX_NUTRIENTS].
+    pub fn fertilize(&mut self, amount: f32) {
+        self.nutrients = (self.nutrients + amount.max(0.0)).min(MAX_NUTRIENTS);
+    }
+}
+
+/// The nutrients that a fauna excretes after eating food with a given
+/// sustenance.
+pub fn excretion(sustenance: f32) -> f32 {
+    sustenance * EXCRETED_FRACTION
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cover_crops_take_up_nutrients() {
+        let mut soil = Soil { nutrients: 1.5 };
+        assert!(soil.grow_cover_crop());
+        assert_eq!(soil.nutrients, 0.5);
+        assert!(!soil.can_grow_cover_crop());
+        assert!(!soil.grow_cover_crop());
+        assert_eq!(soil.nutrients, 0.5);
+    }
+
+    #[test]
+    fn fertilizing_is_capped() {
+        let mut soil = Soil { nutrients: 0.0 };
+        soil.fertilize(excretion(4.0));
+        assert_eq!(soil.nutrients, 1.0);
+        soil.fertilize(100.0);
+        assert_eq!(soil.nutrients, MAX_NUTRIENTS);
+        soil.fertilize(-5.0);
+        assert_eq!(soil.nutrients, MAX_NUTRIENTS);
+    }
+}
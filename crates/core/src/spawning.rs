@@ -0,0 +1,148 @@
+//! Rules for populating the world with fauna and respawning them.
+
+use crate::content::SpawnerDef;
+
+/// The length of a full day in seconds.
+pub const DAY_LENGTH: f32 = 600.0;
+
+/// The time of day as a fraction of a day, where 0 is midnight and 0.5 is
+/// noon, after `clock` seconds since the world was created at midnight.
+pub fn time_of_day(clock: f32) -> f32 {
+    (clock / DAY_LENGTH).fract()
+}
+
+/// The kinds of tiles that a spawner populates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Biome {
+    /// Every tile.
+    Any,
+
+    /// Tiles with a cover crop on them.
+    Grassland,
+
+    /// Tiles with a medium crop on them.
+    Cropland,
+}
+
+impl Biome {
+    /// Whether a tile with the given contents belongs to this biome.
+    pub fn contains(&self, has_cover_crop: bool, has_medium_crop: bool) -> bool {
+        match self {
+            Biome::Any => true,
+            Biome::Grassland => has_cover_crop,
+            Biome::Cropland => has_medium_crop,
+        }
+    }
+}
+
+impl SpawnerDef {
+    /// Whether this spawner is active at a time of day. Hours that end
+    /// before they start wrap around midnight.
+    pub fn is_active(&self, time_of_day: f32) -> bool {
+        let (start, end) = self.hours;
+        if start <= end {
+            (start..end).contains(&time_of_day)
+        } else {
+            time_of_day >= start || time_of_day < end
+        }
+    }
+
+    /// The number of fauna that this spawner populates a number of tiles in
+    /// its biome with. Spawners with any density spawn at least one fauna.
+    pub fn spawn_count(&self, tiles: usize) -> usize {
+        if tiles == 0 || self.density <= 0.0 {
+            return 0;
+        }
+
+        ((self.density * tiles as f32).round() as usize).max(1)
+    }
+}
+
+/// The changing state of a single spawner.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Spawner {
+    /// The time in seconds until this spawner next checks its biome.
+    pub cooldown: f32,
+}
+
+impl Spawner {
+    /// Advances a spawner by `dt` seconds, returning the number of fauna to
+    /// spawn.
+    ///
+    /// Spawners only repopulate their biome when the species has gone
+    /// locally extinct there, which `local_population` counts, and spawn at
+    /// most `room` fauna.
+    pub fn tick(
+        &mut self,
+        def: &SpawnerDef,
+        dt: f32,
+        time_of_day: f32,
+        tiles: usize,
+        local_population: usize,
+        room: usize,
+    ) -> usize {
+        self.cooldown -= dt;
+        if self.cooldown > 0.0 {
+            return 0;
+        }
+
+        self.cooldown = def.interval;
+
+        if local_population > 0 || !def.is_active(time_of_day) {
+            return 0;
+        }
+
+        def.spawn_count(tiles).min(room)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NIGHT: SpawnerDef = SpawnerDef {
+        species: "fox",
+        biome: Biome::Any,
+        density: 0.01,
+        hours: (0.75, 0.25),
+        interval: 10.0,
+    };
+
+    #[test]
+    fn hours_wrap_around_midnight() {
+        assert!(NIGHT.is_active(0.0));
+        assert!(NIGHT.is_active(0.8));
+        assert!(!NIGHT.is_active(0.5));
+
+        let day = SpawnerDef {
+            hours: (0.25, 0.75),
+            ..NIGHT
+        };
+
+        assert!(day.is_active(0.5));
+        assert!(!day.is_active(0.0));
+
+        assert_eq!(time_of_day(DAY_LENGTH * 2.5), 0.5);
+    }
+
+    #[test]
+    fn spawners_only_repopulate_extinct_biomes() {
+        let mut spawner = Spawner::default();
+        assert_eq!(spawner.tick(&NIGHT, 1.0, 0.0, 1024, 0, 40), 10);
+
+        // waits for the interval before checking again
+        assert_eq!(spawner.tick(&NIGHT, 1.0, 0.0, 1024, 0, 40), 0);
+        assert_eq!(spawner.tick(&NIGHT, 9.0, 0.0, 1024, 3, 40), 0);
+        assert_eq!(spawner.tick(&NIGHT, 10.0, 0.5, 1024, 0, 40), 0);
+        assert_eq!(spawner.tick(&NIGHT, 10.0, 0.0, 1024, 0, 4), 4);
+        assert_eq!(spawner.tick(&NIGHT, 10.0, 0.0, 0, 0, 40), 0);
+    }
+
+    #[test]
+    fn biomes_match_tile_contents() {
+        assert!(Biome::Any.contains(false, false));
+        assert!(Biome::Grassland.contains(true, false));
+        assert!(!Biome::Grassland.contains(false, true));
+        assert!(Biome::Cropland.contains(false, true));
+    }
+}
@@ -0,0 +1,35 @@
+//! Checks the game's item, crop, and recipe definitions for data mistakes.
+//!
+//! Exits with a non-zero status if any errors were found.
+
+use std::process::ExitCode;
+
+use flowerpot_tools::report::{check, Content, Severity};
+
+fn main() -> ExitCode {
+    let content = Content::builtin();
+    let report = check(&content);
+
+    println!(
+        "Checked {} items, {} crops, and {} recipes.",
+        content.items.len(),
+        content.crops.len(),
+        content.recipes.len()
+    );
+
+    for finding in report.findings.iter() {
+        println!("{}", finding);
+    }
+
+    println!(
+        "{} error(s), {} warning(s)",
+        report.count(Severity::Error),
+        report.count(Severity::Warning)
+    );
+
+    if report.has_errors() {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
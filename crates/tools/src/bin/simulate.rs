@@ -0,0 +1,144 @@
+//! Runs a headless simulation of the farm's ecosystem and prints population
+//! and coverage counts over time as CSV.
+//!
+//! Example: `cargo run --bin simulate -- --seed 7 --ticks 36000 --bunnies 10`
+
+use std::{error::Error, process::ExitCode};
+
+use flowerpot_core::sim::{Census, SimConfig, Simulation};
+use glam::IVec2;
+
+const HELP: &str = "\
+Runs a headless simulation of the farm's ecosystem and prints CSV to stdout.
+
+Options:
+  --seed <u64>              Random seed [default: 0]
+  --ticks <u64>             Number of ticks to simulate [default: 36000]
+  --sample-every <u64>      Ticks between CSV rows [default: 60]
+  --tick-duration <f32>     Seconds per tick [default: 1/60]
+  --grow-interval <f32>     Seconds between crop spreading ticks [default: 1]
+  --sustenance <f32>        Fullness restored by eating a cover crop [default: 2]
+  --bunnies <usize>         Number of bunnies to spawn [default: 5]
+  --foxes <usize>           Number of foxes to spawn [default: 2]
+  --hive <x,y>              Places a bee hive on a tile (repeatable)
+  --hunger-rate <f32>       Bunny fullness lost per second [default: 0.1]
+  --metabolism <f32>        Bunny stamina regained per second [default: 1]
+  --search-radius <f32>     Bunny food search radius [default: 10]
+  --wariness <f32>          Bunny predator flee radius [default: 3]
+  -h, --help                Prints this message
+";
+
+fn main() -> ExitCode {
+    let mut args = pico_args::Arguments::from_env();
+
+    if args.contains(["-h", "--help"]) {
+        print!("{}", HELP);
+        return ExitCode::SUCCESS;
+    }
+
+    match run(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            eprint!("\n{}", HELP);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(mut args: pico_args::Arguments) -> Result<(), Box<dyn Error>> {
+    let defaults = SimConfig::default();
+    let mut species = defaults.species.clone();
+    for params in species.iter_mut() {
+        match params.name {
+            "bunny" => {
+                let metabolism = &mut params.metabolism;
+                metabolism.hunger_rate = args
+                    .opt_value_from_str("--hunger-rate")?
+                    .unwrap_or(metabolism.hunger_rate);
+                metabolism.passive_metabolism = args
+                    .opt_value_from_str("--metabolism")?
+                    .unwrap_or(metabolism.passive_metabolism);
+                params.search_radius = args
+                    .opt_value_from_str("--search-radius")?
+                    .unwrap_or(params.search_radius);
+                params.wariness = args
+                    .opt_value_from_str("--wariness")?
+                    .unwrap_or(params.wariness);
+                params.count = args
+                    .opt_value_from_str("--bunnies")?
+                    .unwrap_or(params.count);
+            }
+            "fox" => {
+                params.count = args.opt_value_from_str("--foxes")?.unwrap_or(params.count);
+            }
+            _ => {}
+        }
+    }
+
+    let config = SimConfig {
+        seed: args.opt_value_from_str("--seed")?.unwrap_or(defaults.seed),
+        tick_duration: args
+            .opt_value_from_str("--tick-duration")?
+            .unwrap_or(defaults.tick_duration),
+        grow_interval: args
+            .opt_value_from_str("--grow-interval")?
+            .unwrap_or(defaults.grow_interval),
+        sustenance: args
+            .opt_value_from_str("--sustenance")?
+            .unwrap_or(defaults.sustenance),
+        species,
+        hives: args.values_from_fn("--hive", parse_tile)?,
+        ..defaults
+    };
+
+    let ticks: u64 = args.opt_value_from_str("--ticks")?.unwrap_or(36000);
+    let sample_every: u64 = args.opt_value_from_str("--sample-every")?.unwrap_or(60);
+
+    let remaining = args.finish();
+    if !remaining.is_empty() {
+        return Err(format!("unexpected arguments {:?}", remaining).into());
+    }
+
+    let mut sim = Simulation::new(config);
+    let census = sim.census();
+    println!("{}", csv_header(&census));
+    println!("{}", csv_row(&census));
+
+    for tick in 1..=ticks {
+        sim.step();
+        if tick % sample_every.max(1) == 0 || tick == ticks {
+            println!("{}", csv_row(&sim.census()));
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_tile(value: &str) -> Result<IVec2, String> {
+    let parse = |coord: &str| coord.trim().parse::<i32>().map_err(|err| err.to_string());
+    match value.split_once(',') {
+        Some((x, y)) => Ok(IVec2::new(parse(x)?, parse(y)?)),
+        None => Err(format!("expected a tile as x,y but got {:?}", value)),
+    }
+}
+
+fn csv_header(census: &Census) -> String {
+    let mut columns = vec!["tick", "time", "cover_crops", "nutrients"];
+    columns.extend(census.medium_crops.iter().map(|(id, _)| *id));
+    columns.extend(census.fauna.iter().map(|(name, _)| *name));
+    columns.join(",")
+}
+
+fn csv_row(census: &Census) -> String {
+    let mut columns = vec![
+        census.tick.to_string(),
+        format!("{:.3}", census.time),
+        census.cover_crops.to_string(),
+        format!("{:.3}", census.nutrients),
+    ];
+
+    columns.extend(census.medium_crops.iter().map(|(_, n)| n.to_string()));
+    columns.extend(census.fauna.iter().map(|(_, n)| n.to_string()));
+    columns.join(",")
+}
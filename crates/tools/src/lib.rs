@@ -0,0 +1,3 @@
+//! Native tooling for working with Flowerpot's game data outside of the engine.
+
+pub mod report;
@@ -0,0 +1,836 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+
+use flowerpot_core::content::*;
+
+/// A set of content definitions to check.
+pub struct Content<'a> {
+    pub items: &'a [ItemDef],
+    pub crops: &'a [CropDef],
+    pub workstations: &'a [WorkstationDef],
+    pub recipes: &'a [RecipeDef],
+    pub world_items: &'a [WorldItemDef],
+    pub world_workstations: &'a [WorldWorkstationDef],
+    pub world_crops: &'a [WorldCropDef],
+    pub wild_crops: &'a [WildCropDef],
+    pub species: &'a [SpeciesDef],
+    pub spawners: &'a [SpawnerDef],
+}
+
+impl Content<'static> {
+    /// The content that ships with the game.
+    pub fn builtin() -> Self {
+        Self {
+            items: ITEMS,
+            crops: CROPS,
+            workstations: WORKSTATIONS,
+            recipes: RECIPES,
+            world_items: WORLD_ITEMS,
+            world_workstations: WORLD_WORKSTATIONS,
+            world_crops: WORLD_CROPS,
+            wild_crops: WILD_CROPS,
+            species: SPECIES,
+            spawners: SPAWNERS,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// A single problem found in the content.
+pub struct Finding {
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.severity {
+            Severity::Warning => write!(f, "warning: {}", self.message),
+            Severity::Error => write!(f, "error: {}", self.message),
+        }
+    }
+}
+
+/// The results of checking a set of content definitions.
+#[derive(Default)]
+pub struct Report {
+    pub findings: Vec<Finding>,
+}
+
+impl Report {
+    pub fn count(&self, severity: Severity) -> usize {
+        self.findings
+            .iter()
+            .filter(|finding| finding.severity == severity)
+            .count()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.count(Severity::Error) > 0
+    }
+
+    fn warn(&mut self, message: String) {
+        self.findings.push(Finding {
+            severity: Severity::Warning,
+            message,
+        });
+    }
+
+    fn error(&mut self, message: String) {
+        self.findings.push(Finding {
+            severity: Severity::Error,
+            message,
+        });
+    }
+}
+
+/// Checks a set of content definitions for data mistakes.
+pub fn check(content: &Content) -> Report {
+    let mut report = Report::default();
+    let items: HashMap<_, _> = content.items.iter().map(|def| (def.id, def)).collect();
+    let crops: HashMap<_, _> = content.crops.iter().map(|def| (def.id, def)).collect();
+
+    check_duplicate_ids(&mut report, "item", content.items.iter().map(|def| def.id));
+    check_duplicate_ids(&mut report, "crop", content.crops.iter().map(|def| def.id));
+
+    check_duplicate_ids(
+        &mut report,
+        "workstation",
+        content.workstations.iter().map(|def| def.id),
+    );
+
+    check_duplicate_ids(
+        &mut report,
+        "species",
+        content.species.iter().map(|def| def.id),
+    );
+
+    check_references(&mut report, content, &items, &crops);
+    check_duplicate_recipes(&mut report, content);
+    check_recipe_values(&mut report, content);
+    check_item_values(&mut report, content);
+    check_crop_values(&mut report, content);
+    check_fauna_values(&mut report, content);
+    check_sources(&mut report, content);
+    check_reachability(&mut report, content, &items, &crops);
+    check_recipe_cycles(&mut report, content);
+    check_growth_chains(&mut report, content, &items, &crops);
+
+    report
+}
+
+fn check_duplicate_ids<'a>(report: &mut Report, kind: &str, ids: impl Iterator<Item = &'a str>) {
+    let mut seen = HashSet::new();
+    for id in ids {
+        if !seen.insert(id) {
+            report.error(format!("duplicate {} ID {:?}", kind, id));
+        }
+    }
+}
+
+fn check_references(
+    report: &mut Report,
+    content: &Content,
+    items: &HashMap<&str, &ItemDef>,
+    crops: &HashMap<&str, &CropDef>,
+) {
+    let mut check_item = |owner: String, id: Option<&str>| {
+        if let Some(id) = id.filter(|id| !items.contains_key(id)) {
+            report.error(format!("{} references unknown item {:?}", owner, id));
+        }
+    };
+
+    for def in content.crops {
+        check_item(format!("crop {:?}", def.id), def.harvest_item);
+    }
+
+    for (index, def) in content.recipes.iter().enumerate() {
+        let owner = || format!("recipe #{}", index);
+        check_item(owner(), Some(def.primary_ingredient));
+        check_item(owner(), def.secondary_ingredient);
+        for item in yielded_items(def) {
+            check_item(owner(), Some(item));
+        }
+    }
+
+    for def in content.world_items {
+        check_item("world".to_string(), Some(def.item));
+    }
+
+    for def in content.species {
+        let owner = || format!("species {:?}", def.id);
+        check_item(owner(), def.taming_food);
+        check_item(owner(), def.produce);
+    }
+
+    let mut check_crop = |owner: String, id: Option<&str>| {
+        if let Some(id) = id.filter(|id| !crops.contains_key(id)) {
+            report.error(format!("{} references unknown crop {:?}", owner, id));
+        }
+    };
+
+    for def in content.items {
+        check_crop(format!("item {:?}", def.id), def.plantable_crop);
+    }
+
+    for def in content.crops {
+        let owner = || format!("crop {:?}", def.id);
+        check_crop(owner(), def.next_growth_phase);
+        check_crop(owner(), def.seed);
+        check_crop(owner(), def.damaged_phase);
+    }
+
+    for def in content.world_crops {
+        check_crop("world".to_string(), Some(def.crop));
+    }
+
+    for def in content.wild_crops {
+        check_crop("wild crops".to_string(), Some(def.crop));
+    }
+
+    let workstations: HashSet<_> = content.workstations.iter().map(|def| def.id).collect();
+    let mut check_workstation = |owner: String, id: Option<&str>| {
+        if let Some(id) = id.filter(|id| !workstations.contains(id)) {
+            report.error(format!("{} references unknown workstation {:?}", owner, id));
+        }
+    };
+
+    for (index, def) in content.recipes.iter().enumerate() {
+        check_workstation(format!("recipe #{}", index), def.workstation);
+    }
+
+    for def in content.world_workstations {
+        check_workstation("world".to_string(), Some(def.workstation));
+    }
+}
+
+fn check_duplicate_recipes(report: &mut Report, content: &Content) {
+    let mut seen = HashMap::new();
+    for (index, def) in content.recipes.iter().enumerate() {
+        let mut key = [Some(def.primary_ingredient), def.secondary_ingredient];
+        key.sort();
+
+        if let Some(first) = seen.insert(key, index) {
+            report.error(format!(
+                "recipe #{} has the same ingredients as recipe #{}",
+                index, first
+            ));
+        }
+    }
+}
+
+fn check_recipe_values(report: &mut Report, content: &Content) {
+    for (index, def) in content.recipes.iter().enumerate() {
+        if def.duration.is_nan() || def.duration < 0.0 {
+            report.error(format!(
+                "recipe #{} has an invalid duration of {}",
+                index, def.duration
+            ));
+        }
+
+        let tables = [
+            ("primary", def.primary_yield),
+            ("secondary", def.secondary_yield),
+        ];
+        for (table_name, table) in tables {
+            for outcome in table {
+                let name = outcome.item.unwrap_or("nothing");
+
+                if outcome.weight.is_nan() || outcome.weight < 0.0 {
+                    report.error(format!(
+                        "recipe #{} yields {:?} with an invalid weight of {}",
+                        index, name, outcome.weight
+                    ));
+                }
+
+                if outcome.min_quantity > outcome.max_quantity {
+                    report.error(format!(
+                        "recipe #{} yields {:?} with a minimum quantity above its maximum",
+                        index, name
+                    ));
+                }
+            }
+
+            let can_roll = table
+                .iter()
+                .any(|outcome| outcome.weight > 0.0 || outcome.quality_weight > 0.0);
+
+            if !table.is_empty() && !can_roll {
+                report.error(format!(
+                    "recipe #{}'s {} yield table has no outcome with a positive weight",
+                    index, table_name
+                ));
+            }
+        }
+    }
+}
+
+fn check_crop_values(report: &mut Report, content: &Content) {
+    for def in content.crops {
+        if !(0.0..=1.0).contains(&def.pest_attractiveness) {
+            report.error(format!(
+                "crop {:?} has an invalid pest attractiveness of {}",
+                def.id, def.pest_attractiveness
+            ));
+        }
+
+        if def.sustenance.is_nan() || def.sustenance < 0.0 {
+            report.error(format!(
+                "crop {:?} has an invalid sustenance of {}",
+                def.id, def.sustenance
+            ));
+        }
+
+        if def.nectar.is_nan() || def.nectar < 0.0 {
+            report.error(format!(
+                "crop {:?} has an invalid nectar of {}",
+                def.id, def.nectar
+            ));
+        }
+
+        if def.nectar > 0.0 && def.seed.is_none() {
+            report.warn(format!(
+                "crop {:?} is flowering but has no seed to benefit from pollination",
+                def.id
+            ));
+        }
+    }
+
+    for def in content.wild_crops {
+        if !(0.0..=1.0).contains(&def.density) {
+            report.error(format!(
+                "wild crop {:?} has an invalid density of {}",
+                def.crop, def.density
+            ));
+        }
+    }
+}
+
+fn check_item_values(report: &mut Report, content: &Content) {
+    for def in content.items {
+        if def.fertilizer.is_nan() || def.fertilizer < 0.0 {
+            report.error(format!(
+                "item {:?} has an invalid fertilizer of {}",
+                def.id, def.fertilizer
+            ));
+        }
+    }
+}
+
+fn check_fauna_values(report: &mut Report, content: &Content) {
+    for def in content.species {
+        let (min_cost, max_cost) = def.movement_cost;
+        if !(min_cost > 0.0 && min_cost <= max_cost) {
+            report.error(format!(
+                "species {:?} has an invalid movement cost range of {}..{}",
+                def.id, min_cost, max_cost
+            ));
+        }
+
+        if def.instincts.hungry_fullness > def.instincts.sated_fullness {
+            report.error(format!(
+                "species {:?} is hungry above the fullness it's sated at",
+                def.id
+            ));
+        }
+
+        if def.footprint.is_nan() || def.footprint < 0.0 {
+            report.error(format!(
+                "species {:?} has an invalid footprint of {}",
+                def.id, def.footprint
+            ));
+        }
+
+        if def.remains.is_nan() || def.remains < 0.0 {
+            report.error(format!(
+                "species {:?} has invalid remains of {}",
+                def.id, def.remains
+            ));
+        }
+
+        if def.life_cycle.maturity_age > def.life_cycle.max_age {
+            report.error(format!(
+                "species {:?} dies of old age before it matures",
+                def.id
+            ));
+        }
+    }
+
+    for def in content.spawners {
+        if !content
+            .species
+            .iter()
+            .any(|species| species.id == def.species)
+        {
+            report.error(format!(
+                "spawner references unknown species {:?}",
+                def.species
+            ));
+        }
+
+        let (start, end) = def.hours;
+        if !(0.0..=1.0).contains(&start) || !(0.0..=1.0).contains(&end) {
+            report.error(format!(
+                "spawner for {:?} has invalid hours of {}..{}",
+                def.species, start, end
+            ));
+        }
+
+        if def.density.is_nan() || def.density < 0.0 {
+            report.error(format!(
+                "spawner for {:?} has an invalid density of {}",
+                def.species, def.density
+            ));
+        }
+    }
+}
+
+/// Helper function to iterate over every item that a recipe may yield.
+fn yielded_items(def: &RecipeDef) -> impl Iterator<Item = &'static str> {
+    let outcomes = def.primary_yield.iter().chain(def.secondary_yield);
+    outcomes.filter_map(|outcome| outcome.item)
+}
+
+fn check_sources(report: &mut Report, content: &Content) {
+    let mut sourced = HashSet::new();
+    sourced.extend(content.world_items.iter().map(|def| def.item));
+    sourced.extend(content.crops.iter().filter_map(|def| def.harvest_item));
+    sourced.extend(content.species.iter().filter_map(|def| def.produce));
+
+    for def in content.recipes {
+        sourced.extend(yielded_items(def));
+    }
+
+    for def in content.items {
+        if !sourced.contains(def.id) {
+            report.warn(format!("item {:?} has no source", def.id));
+        }
+    }
+}
+
+fn check_reachability(
+    report: &mut Report,
+    content: &Content,
+    items: &HashMap<&str, &ItemDef>,
+    crops: &HashMap<&str, &CropDef>,
+) {
+    let mut reachable_items: HashSet<&str> = HashSet::new();
+    let mut reachable_crops: HashSet<&str> = HashSet::new();
+    reachable_items.extend(content.world_items.iter().map(|def| def.item));
+    reachable_crops.extend(content.world_crops.iter().map(|def| def.crop));
+    reachable_crops.extend(
+        content
+            .wild_crops
+            .iter()
+            .filter(|def| def.density > 0.0)
+            .map(|def| def.crop),
+    );
+
+    let placed_workstations: HashSet<_> = content
+        .world_workstations
+        .iter()
+        .map(|def| def.workstation)
+        .collect();
+
+    // expand the reachable sets until a fixed point is reached
+    loop {
+        let old_len = reachable_items.len() + reachable_crops.len();
+
+        for id in reachable_crops.clone() {
+            let Some(def) = crops.get(id) else { continue };
+            reachable_crops.extend(def.next_growth_phase);
+            reachable_crops.extend(def.seed);
+            reachable_crops.extend(def.damaged_phase);
+            reachable_items.extend(def.harvest_item);
+        }
+
+        for id in reachable_items.clone() {
+            let Some(def) = items.get(id) else { continue };
+            reachable_crops.extend(def.plantable_crop);
+        }
+
+        for def in content.recipes {
+            let has_primary = reachable_items.contains(def.primary_ingredient);
+            let has_secondary = def
+                .secondary_ingredient
+                .is_none_or(|id| reachable_items.contains(id));
+
+            let has_workstation = def
+                .workstation
+                .is_none_or(|id| placed_workstations.contains(id));
+
+            if has_primary && has_secondary && has_workstation {
+                reachable_items.extend(yielded_items(def));
+            }
+        }
+
+        // livestock produce items once they're tamed
+        for def in content.species {
+            let spawned = content
+                .spawners
+                .iter()
+                .any(|spawner| spawner.species == def.id);
+            let tamed = def
+                .taming_food
+                .is_some_and(|id| reachable_items.contains(id));
+
+            if spawned && tamed {
+                reachable_items.extend(def.produce);
+            }
+        }
+
+        if reachable_items.len() + reachable_crops.len() == old_len {
+            break;
+        }
+    }
+
+    for def in content.items {
+        if !reachable_items.contains(def.id) {
+            report.warn(format!("item {:?} is unreachable from the world", def.id));
+        }
+    }
+
+    for def in content.crops {
+        if !reachable_crops.contains(def.id) {
+            report.warn(format!("crop {:?} is unreachable from the world", def.id));
+        }
+    }
+
+    for def in content.workstations {
+        if !placed_workstations.contains(def.id) {
+            report.warn(format!("workstation {:?} is never placed", def.id));
+        }
+    }
+}
+
+fn check_recipe_cycles(report: &mut Report, content: &Content) {
+    let mut edges: HashMap<&str, BTreeSet<&str>> = HashMap::new();
+    for def in content.recipes {
+        let ingredients = [Some(def.primary_ingredient), def.secondary_ingredient];
+        for ingredient in ingredients.into_iter().flatten() {
+            let targets = edges.entry(ingredient).or_default();
+            targets.extend(yielded_items(def));
+        }
+    }
+
+    let mut cycles = BTreeSet::new();
+    for def in content.items {
+        let mut path = vec![def.id];
+        find_cycles(&edges, &mut path, &mut cycles);
+    }
+
+    for cycle in cycles {
+        report.warn(format!("recipes form a cycle: {}", cycle.join(" -> ")));
+    }
+}
+
+/// Depth-first search for cycles that return to the first node of `path`.
+///
+/// Cycles are only recorded when started from their smallest ID so that each
+/// cycle is reported once.
+fn find_cycles<'a>(
+    edges: &HashMap<&'a str, BTreeSet<&'a str>>,
+    path: &mut Vec<&'a str>,
+    cycles: &mut BTreeSet<Vec<&'a str>>,
+) {
+    let start = path[0];
+    let Some(targets) = edges.get(path[path.len() - 1]) else {
+        return;
+    };
+    for target in targets.iter().copied() {
+        if target == start {
+            let mut cycle = path.clone();
+            cycle.push(start);
+            cycles.insert(cycle);
+        } else if target > start && !path.contains(&target) {
+            path.push(target);
+            find_cycles(edges, path, cycles);
+            path.pop();
+        }
+    }
+}
+
+fn check_growth_chains(
+    report: &mut Report,
+    content: &Content,
+    items: &HashMap<&str, &ItemDef>,
+    crops: &HashMap<&str, &CropDef>,
+) {
+    let mut loops = BTreeSet::new();
+    for def in content.crops {
+        let mut chain = vec![def.id];
+        let mut current = def;
+        while let Some(next) = current.next_growth_phase {
+            if let Some(start) = chain.iter().position(|id| *id == next) {
+                let mut cycle = chain[start..].to_vec();
+                cycle.sort();
+                loops.insert(cycle);
+                break;
+            }
+
+            let Some(next) = crops.get(next) else { break };
+            chain.push(next.id);
+            current = *next;
+        }
+    }
+
+    for cycle in loops {
+        report.error(format!(
+            "growth chain never terminates: {}",
+            cycle.join(", ")
+        ));
+    }
+
+    // only check chains from their first phase
+    let later_phases: HashSet<_> = content
+        .crops
+        .iter()
+        .filter_map(|def| def.next_growth_phase)
+        .collect();
+
+    for def in content.crops {
+        if later_phases.contains(def.id) {
+            continue;
+        }
+
+        let mut visited = HashSet::new();
+        let mut yields_seed = false;
+        let mut current = Some(def);
+        while let Some(phase) = current.filter(|phase| visited.insert(phase.id)) {
+            let plantable_harvest = phase
+                .harvest_item
+                .and_then(|id| items.get(id))
+                .is_some_and(|item| item.plantable_crop.is_some());
+
+            yields_seed |= phase.seed.is_some() || plantable_harvest;
+            current = phase
+                .next_growth_phase
+                .and_then(|id| crops.get(id).copied());
+        }
+
+        if !yields_seed {
+            report.warn(format!(
+                "growth chain starting at crop {:?} never yields a seed",
+                def.id
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_content_has_no_errors() {
+        let report = check(&Content::builtin());
+        let errors: Vec<_> = report
+            .findings
+            .iter()
+            .filter(|finding| finding.severity == Severity::Error)
+            .map(|finding| finding.to_string())
+            .collect();
+
+        assert!(errors.is_empty(), "{:#?}", errors);
+    }
+
+    /// Helper function to check whether a report has a finding with a message.
+    fn has_finding(report: &Report, severity: Severity, message: &str) -> bool {
+        report
+            .findings
+            .iter()
+            .any(|finding| finding.severity == severity && finding.message == message)
+    }
+
+    #[test]
+    fn builtin_crops_are_reachable() {
+        let report = check(&Content::builtin());
+        let unreachable: Vec<_> = report
+            .findings
+            .iter()
+            .filter(|finding| finding.message.contains("unreachable"))
+            .map(|finding| finding.to_string())
+            .collect();
+
+        assert!(unreachable.is_empty(), "{:#?}", unreachable);
+        assert!(!has_finding(
+            &report,
+            Severity::Warning,
+            "crop \"iris\" is unreachable from the world"
+        ));
+    }
+
+    #[test]
+    fn duplicate_recipes_are_errors() {
+        const RECIPES: &[RecipeDef] = &[
+            RecipeDef {
+                secondary_ingredient: Some("maize"),
+                primary_yield: &[YieldDef::new("cornmeal")],
+                ..RecipeDef::new("maize_seeds")
+            },
+            RecipeDef {
+                secondary_ingredient: Some("maize_seeds"),
+                primary_yield: &[YieldDef::new("maize_seeds")],
+                ..RecipeDef::new("maize")
+            },
+        ];
+
+        let content = Content {
+            recipes: RECIPES,
+            ..Content::builtin()
+        };
+
+        let report = check(&content);
+        assert!(has_finding(
+            &report,
+            Severity::Error,
+            "recipe #1 has the same ingredients as recipe #0"
+        ));
+    }
+
+    #[test]
+    fn unreachable_content_is_warned_about() {
+        const WORLD_ITEMS: &[WorldItemDef] = &[];
+        const WORLD_CROPS: &[WorldCropDef] = &[];
+        const WILD_CROPS: &[WildCropDef] = &[WildCropDef {
+            crop: "iris",
+            density: 0.0,
+        }];
+
+        let content = Content {
+            world_items: WORLD_ITEMS,
+            world_crops: WORLD_CROPS,
+            wild_crops: WILD_CROPS,
+            ..Content::builtin()
+        };
+
+        let report = check(&content);
+        assert!(has_finding(
+            &report,
+            Severity::Warning,
+            "item \"maize_seeds\" is unreachable from the world"
+        ));
+        assert!(has_finding(
+            &report,
+            Severity::Warning,
+            "crop \"iris\" is unreachable from the world"
+        ));
+    }
+
+    #[test]
+    fn items_without_sources_are_warned_about() {
+        const ITEMS: &[ItemDef] = &[ItemDef::new("orphan", "Orphan")];
+        const WORLD_ITEMS: &[WorldItemDef] = &[];
+        const RECIPES: &[RecipeDef] = &[];
+        const CROPS: &[CropDef] = &[];
+        const SPECIES: &[SpeciesDef] = &[];
+        const SPAWNERS: &[SpawnerDef] = &[];
+        const WORLD_CROPS: &[WorldCropDef] = &[];
+        const WILD_CROPS: &[WildCropDef] = &[];
+        const WORLD_WORKSTATIONS: &[WorldWorkstationDef] = &[];
+
+        let content = Content {
+            items: ITEMS,
+            world_items: WORLD_ITEMS,
+            recipes: RECIPES,
+            crops: CROPS,
+            species: SPECIES,
+            spawners: SPAWNERS,
+            world_crops: WORLD_CROPS,
+            wild_crops: WILD_CROPS,
+            world_workstations: WORLD_WORKSTATIONS,
+            ..Content::builtin()
+        };
+
+        let report = check(&content);
+        assert!(has_finding(
+            &report,
+            Severity::Warning,
+            "item \"orphan\" has no source"
+        ));
+    }
+
+    #[test]
+    fn recipe_cycles_are_warned_about() {
+        const RECIPES: &[RecipeDef] = &[
+            RecipeDef {
+                primary_yield: &[YieldDef::new("cornmeal")],
+                ..RecipeDef::new("maize")
+            },
+            RecipeDef {
+                primary_yield: &[YieldDef::new("maize")],
+                ..RecipeDef::new("cornmeal")
+            },
+        ];
+
+        let content = Content {
+            recipes: RECIPES,
+            ..Content::builtin()
+        };
+
+        let report = check(&content);
+        assert!(has_finding(
+            &report,
+            Severity::Warning,
+            "recipes form a cycle: cornmeal -> maize -> cornmeal"
+        ));
+    }
+
+    #[test]
+    fn seedless_growth_chains_are_warned_about() {
+        const CROPS: &[CropDef] = &[
+            CropDef {
+                next_growth_phase: Some("b"),
+                ..CropDef::new("a", "")
+            },
+            CropDef::new("b", ""),
+        ];
+
+        let content = Content {
+            crops: CROPS,
+            ..Content::builtin()
+        };
+
+        let report = check(&content);
+        assert!(has_finding(
+            &report,
+            Severity::Warning,
+            "growth chain starting at crop \"a\" never yields a seed"
+        ));
+        assert!(!has_finding(
+            &report,
+            Severity::Warning,
+            "growth chain starting at crop \"b\" never yields a seed"
+        ));
+    }
+
+    #[test]
+    fn growth_loops_are_errors() {
+        const CROPS: &[CropDef] = &[
+            CropDef {
+                next_growth_phase: Some("b"),
+                ..CropDef::new("a", "")
+            },
+            CropDef {
+                next_growth_phase: Some("a"),
+                ..CropDef::new("b", "")
+            },
+        ];
+
+        let content = Content {
+            crops: CROPS,
+            ..Content::builtin()
+        };
+
+        let report = check(&content);
+        assert!(report
+            .findings
+            .iter()
+            .any(|finding| finding.message == "growth chain never terminates: a, b"));
+    }
+}
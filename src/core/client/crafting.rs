@@ -0,0 +1,30 @@
+use ambient_api::{
+    components::core::{
+        primitives::cube,
+        rendering::color,
+        transform::{local_to_parent, local_to_world, translation},
+    },
+    prelude::*,
+};
+
+use crate::components::{crafting::workstation_class_ref, map};
+
+pub fn init_crafting() {
+    // give workstations a placeholder model
+    spawn_query((map::position(), workstation_class_ref())).bind(move |workstations| {
+        for (e, (_map_pos, class)) in workstations {
+            entity::add_component(e, local_to_world(), Default::default());
+
+            let mut model = Entity::new()
+                .with_default(local_to_parent())
+                .with_default(cube())
+                .with(translation(), Vec3::Z * 0.5);
+
+            if let Some(class_color) = entity::get_component(class, color()) {
+                model.set(color(), class_color);
+            }
+
+            entity::add_child(e, model.spawn());
+        }
+    });
+}
@@ -0,0 +1,69 @@
+use ambient_api::{
+    components::core::{
+        primitives::cube,
+        rendering::color,
+        transform::{local_to_parent, local_to_world, scale, translation},
+    },
+    prelude::*,
+};
+use flowerpot_core::{content, husbandry::PEN_RADIUS};
+
+use crate::{
+    components::{fauna, hive, map, pen, species},
+    shared::partitioning::Partitioning,
+};
+
+/// Sets up fauna models. Returns the index of fauna on the map.
+pub fn init_fauna() -> Partitioning {
+    // give hives a placeholder model
+    spawn_query((map::position(), hive())).bind(move |hives| {
+        for (e, (_map_pos, _)) in hives {
+            entity::add_component(e, local_to_world(), Default::default());
+
+            let model = Entity::new()
+                .with_default(local_to_parent())
+                .with_default(cube())
+                .with(translation(), Vec3::Z * 0.3)
+                .with(scale(), Vec3::splat(0.6))
+                .with(color(), vec4(1.0, 0.8, 0.2, 1.0));
+
+            entity::add_child(e, model.spawn());
+        }
+    });
+
+    // give pens a placeholder model that covers their area
+    spawn_query((map::position(), pen())).bind(move |pens| {
+        for (e, (_map_pos, _)) in pens {
+            entity::add_component(e, local_to_world(), Default::default());
+
+            let model = Entity::new()
+                .with_default(local_to_parent())
+                .with_default(cube())
+                .with(translation(), Vec3::Z * 0.05)
+                .with(scale(), vec3(PEN_RADIUS * 2.0, PEN_RADIUS * 2.0, 0.1))
+                .with(color(), vec4(0.6, 0.4, 0.2, 0.5));
+
+            entity::add_child(e, model.spawn());
+        }
+    });
+
+    // give species without a model prefab a placeholder model sized to their
+    // footprint
+    spawn_query((fauna(), species())).bind(move |animals| {
+        for (e, (_, id)) in animals {
+            let Some(def) = content::species(&id) else { continue };
+            if def.prefab_url.is_some() {
+                continue;
+            }
+
+            let model = Entity::new()
+                .with_default(cube())
+                .with(scale(), Vec3::splat(def.footprint * def.scale * 1.5))
+                .with(color(), Vec4::from_array(def.color));
+
+            entity::add_components(e, model);
+        }
+    });
+
+    Partitioning::new(fauna())
+}
@@ -0,0 +1,117 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use ambient_api::{
+    components::core::{player::player, transform::translation},
+    messages::Frame,
+    prelude::*,
+};
+use flowerpot_core::interpolation::{Snapshot, SnapshotBuffer, INTERPOLATION_DELAY};
+
+use crate::{
+    components::{
+        fauna,
+        map::{elevation, position},
+        player::{local_player, pitch, yaw},
+    },
+    player::update_player_yaw_pitch,
+};
+
+/// The snapshots of remote entities received so far.
+#[derive(Default)]
+struct Interpolation {
+    /// Seconds since interpolation started.
+    time: f32,
+    buffers: HashMap<EntityId, SnapshotBuffer>,
+}
+
+impl Interpolation {
+    /// Records an entity's current networked transform.
+    fn record(&mut self, e: EntityId) {
+        let Some(xy) = entity::get_component(e, position()) else { return };
+        let elevation = entity::get_component(e, elevation()).unwrap_or(0.0);
+        let snapshot = Snapshot {
+            translation: xy.extend(elevation),
+            yaw: entity::get_component(e, yaw()).unwrap_or(0.0),
+            pitch: entity::get_component(e, pitch()).unwrap_or(0.0),
+        };
+
+        self.buffers.entry(e).or_default().push(self.time, snapshot);
+    }
+}
+
+/// Moves remote players and fauna smoothly by rendering them slightly in the
+/// past, between the transforms received from the server.
+pub fn init_interpolation() {
+    let state = Arc::new(Mutex::new(Interpolation::default()));
+
+    // record remote players' movement and looking around
+    change_query((player(), position(), yaw(), pitch()))
+        .track_change((position(), yaw(), pitch()))
+        .excludes(local_player())
+        .bind({
+            let state = state.clone();
+            move |players| {
+                let mut state = state.lock().unwrap();
+                for (e, _) in players {
+                    state.record(e);
+                }
+            }
+        });
+
+    // record fauna wandering and hopping
+    change_query((fauna(), position()))
+        .track_change((position(), elevation()))
+        .bind({
+            let state = state.clone();
+            move |fauna| {
+                let mut state = state.lock().unwrap();
+                for (e, _) in fauna {
+                    state.record(e);
+                }
+            }
+        });
+
+    // forget despawned entities
+    despawn_query((player(), position())).bind({
+        let state = state.clone();
+        move |players| {
+            let mut state = state.lock().unwrap();
+            for (e, _) in players {
+                state.buffers.remove(&e);
+            }
+        }
+    });
+
+    despawn_query((fauna(), position())).bind({
+        let state = state.clone();
+        move |fauna| {
+            let mut state = state.lock().unwrap();
+            for (e, _) in fauna {
+                state.buffers.remove(&e);
+            }
+        }
+    });
+
+    // blend each entity's snapshots at the delayed time
+    Frame::subscribe(move |_| {
+        let mut state = state.lock().unwrap();
+        state.time += delta_time();
+        let time = state.time - INTERPOLATION_DELAY;
+
+        // the local player may be recorded before it's known to be local
+        state
+            .buffers
+            .retain(|e, _| !entity::has_component(*e, local_player()));
+
+        for (e, buffer) in state.buffers.iter_mut() {
+            let Some(snapshot) = buffer.sample(time) else { continue };
+            entity::add_component(*e, translation(), snapshot.translation);
+            if entity::has_component(*e, player()) {
+                update_player_yaw_pitch(*e, snapshot.yaw, snapshot.pitch);
+            }
+        }
+    });
+}
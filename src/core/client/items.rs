@@ -3,19 +3,33 @@ use ambient_api::{
         ecs::children,
         prefab::prefab_from_url,
         primitives::cube,
-        rendering::color,
+        rendering::{color, outline_recursive},
         transform::{local_to_parent, local_to_world},
     },
     prelude::*,
 };
 
-use crate::components::{items, map, player};
+use crate::{
+    components::{items, map, player},
+    shared::partitioning::Partitioning,
+};
+
+/// The outline color of items that the local player can pick up.
+const REACH_OUTLINE: Vec4 = Vec4::new(1.0, 0.8, 0.2, 1.0);
+
+/// Sets up item models. Returns the index of items on the map.
+pub fn init_items() -> Partitioning {
+    let item_index = Partitioning::new(items::class_ref());
 
-pub fn init_items() {
-    crate::shared::partitioning::init_qbvh(
-        items::class_ref(),
+    // outline items within the local player's reach
+    item_index.track_within_radius(
         items::search_radius(),
-        items::search_result(),
+        |_player, item| entity::add_component(item, outline_recursive(), REACH_OUTLINE),
+        |_player, item| {
+            if entity::has_component(item, outline_recursive()) {
+                entity::remove_component(item, outline_recursive());
+            }
+        },
     );
 
     change_query(player::held_item_ref())
@@ -40,6 +54,8 @@ pub fn init_items() {
             entity::despawn_recursive(e);
         }
     });
+
+    item_index
 }
 
 /// Helper function to spawn models of items.
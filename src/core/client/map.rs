@@ -2,14 +2,15 @@ use std::{collections::HashMap, sync::Mutex};
 
 use ambient_api::{
     components::core::{
-        primitives::quad, rendering::pbr_material_from_url, transform::translation,
+        player::player, primitives::quad, rendering::pbr_material_from_url, transform::translation,
     },
     concepts::make_transformable,
     glam::IVec2,
     prelude::*,
 };
+use flowerpot_core::map::position_to_tile;
 
-use crate::components::{cover_crop_occupant, map::*};
+use crate::components::{cover_crop_occupant, fauna, map::*};
 
 lazy_static::lazy_static! {
     pub static ref MAP: Mutex<HashMap<IVec2, EntityId>> = Mutex::new(HashMap::new());
@@ -23,9 +24,11 @@ pub fn init_map() {
         }
     });
 
-    // update entities' translation with map_position
+    // update entities' translation with map_position, except for players and
+    // fauna, which are moved by their predicted or interpolated positions
     change_query(position())
         .track_change(position())
+        .excludes((player(), fauna()))
         .bind(move |changes| {
             for (e, xy) in changes {
                 update_transform(e, xy);
@@ -48,8 +51,7 @@ pub fn init_map() {
     spawn_query((tile(), position())).bind(move |tiles| {
         let mut map = MAP.lock().unwrap();
         for (tile, (_, xy)) in tiles {
-            let xy = (xy + 0.5).floor().as_ivec2();
-            map.insert(xy, tile);
+            map.insert(position_to_tile(xy), tile);
         }
     });
 
@@ -57,8 +59,7 @@ pub fn init_map() {
     despawn_query((tile(), position())).bind(move |tiles| {
         let mut map = MAP.lock().unwrap();
         for (_tile, (_, xy)) in tiles {
-            let xy = (xy + 0.5).floor().as_ivec2();
-            map.remove(&xy);
+            map.remove(&position_to_tile(xy));
         }
     });
 
@@ -85,6 +86,17 @@ pub fn init_map() {
                 }
             }
         });
+
+    // reset the materials of tiles whose cover crops were eaten
+    despawn_query((tile(), cover_crop_occupant())).bind(move |tiles| {
+        for (e, _) in tiles {
+            entity::add_component(
+                e,
+                pbr_material_from_url(),
+                asset::url("assets/materials/materials/pipeline.toml/0/mat.json").unwrap(),
+            );
+        }
+    });
 }
 
 /// Helper function to update a map-positioned transform.
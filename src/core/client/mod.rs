@@ -1,4 +1,7 @@
+mod crafting;
 mod crops;
+mod fauna;
+mod interpolation;
 mod items;
 mod map;
 mod player;
@@ -8,8 +11,11 @@ mod shared;
 
 #[ambient_api::main]
 pub async fn main() {
+    crafting::init_crafting();
     crops::init_crops();
-    items::init_items();
+    let fauna = fauna::init_fauna();
+    let items = items::init_items();
     map::init_map();
-    player::init_players().await;
+    interpolation::init_interpolation();
+    player::init_players(fauna, items).await;
 }
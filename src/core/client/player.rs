@@ -11,21 +11,31 @@ use ambient_api::{
         transform::{local_to_parent, local_to_world, rotation, scale, translation},
     },
     concepts::{make_perspective_infinite_reverse_camera, make_transformable},
+    glam::IVec2,
     input::get_previous,
     messages::Frame,
     prelude::*,
 };
+use flowerpot_core::{
+    map::position_to_tile,
+    player::{Prediction, DEFAULT_MOVEMENT_SPEED},
+};
 
 use crate::{
-    components::{crops::medium_occupant_ref, player::*},
+    components::{crops::medium_occupant_ref, map, player::*},
     messages::*,
+    shared::{map::blocks_players, partitioning::Partitioning},
 };
 
-// TODO make this a component?
+// TODO make these components?
 const HEAD_HEIGHT: f32 = 1.5;
+const FAUNA_HEIGHT: f32 = 0.5;
 
 /// Initializes player-related systems. Returns the local player entity ID.
-pub async fn init_players() -> EntityId {
+///
+/// Takes the indices of fauna and items on the map, for targeting fauna and
+/// picking up items.
+pub async fn init_players(fauna: Partitioning, items: Partitioning) -> EntityId {
     on_player_spawn(|player_entity, user, is_local_player| {
         let init_hand = |parent, hand_ref, offset| {
             let e = entity::get_component(player_entity, hand_ref)
@@ -99,15 +109,6 @@ pub async fn init_players() -> EntityId {
         }
     });
 
-    change_query((player(), yaw(), pitch()))
-        .track_change((yaw(), pitch()))
-        .excludes(local_player())
-        .bind(move |players| {
-            for (e, (_player, yaw, pitch)) in players {
-                update_player_yaw_pitch(e, yaw, pitch);
-            }
-        });
-
     change_query((player(), local_player(), local_yaw(), local_pitch()))
         .track_change((local_yaw(), local_pitch()))
         .bind(move |players| {
@@ -124,35 +125,76 @@ pub async fn init_players() -> EntityId {
         let mut cursor_lock = input::CursorLockGuard::new(true);
         let mut pitch = 0.0;
         let mut yaw = 0.0;
+        let mut prediction: Option<Prediction> = None;
+        let mut last_authoritative = None;
         move |_| {
             let input = input::get();
             if !cursor_lock.auto_unlock_on_escape(&input) {
                 return;
             }
 
+            // only the direction matters, since inputs are normalized and then
+            // applied at the player's walk speed
             let mut direction = Vec2::ZERO;
-            let speed = 1.0; // always 1.0 because PlayerMovementInput is normalized
             if input.keys.contains(&KeyCode::W) {
-                direction.y -= speed;
+                direction.y -= 1.0;
             }
             if input.keys.contains(&KeyCode::S) {
-                direction.y += speed;
+                direction.y += 1.0;
             }
             if input.keys.contains(&KeyCode::A) {
-                direction.x -= speed;
+                direction.x -= 1.0;
             }
             if input.keys.contains(&KeyCode::D) {
-                direction.x += speed;
+                direction.x += 1.0;
             }
 
-            let direction = direction.normalize();
-
             let pitch_factor = 0.01;
             let yaw_factor = 0.01;
             yaw = (yaw + input.mouse_delta.x * yaw_factor) % TAU;
             pitch = (pitch + input.mouse_delta.y * pitch_factor).clamp(-FRAC_PI_2, FRAC_PI_2);
 
-            PlayerMovementInput::new(direction, pitch, yaw).send_server_reliable();
+            // replay unapplied inputs whenever the server's position comes in
+            let Some(position) = entity::get_component(local_player_entity, map::position()) else {
+                return;
+            };
+
+            let sequence =
+                entity::get_component(local_player_entity, input_sequence()).unwrap_or(0);
+            let walk_speed = entity::get_component(local_player_entity, movement_speed())
+                .unwrap_or(DEFAULT_MOVEMENT_SPEED);
+
+            let prediction = prediction.get_or_insert_with(|| Prediction::new(position));
+            let movement = {
+                let map = crate::map::MAP.lock().unwrap();
+                let is_blocked = |xy: IVec2| blocks_players(map.get(&xy).copied());
+                if last_authoritative != Some((position, sequence)) {
+                    last_authoritative = Some((position, sequence));
+                    prediction.reconcile(position, sequence, walk_speed, is_blocked);
+                }
+
+                prediction.predict(direction, yaw, pitch, delta_time(), walk_speed, is_blocked)
+            };
+
+            entity::add_component(
+                local_player_entity,
+                translation(),
+                prediction.position.extend(0.0),
+            );
+
+            // send every input that the server hasn't applied yet, so that
+            // lost messages are made up for by the next ones, or by resending
+            // them while standing still
+            if let Some(movement) = movement {
+                let batch: Vec<_> = prediction.unacknowledged().collect();
+                PlayerMovementInput::new(
+                    batch.iter().map(|input| input.direction).collect(),
+                    batch.iter().map(|input| input.dt).collect(),
+                    batch.iter().map(|input| input.look()).collect(),
+                    movement.sequence,
+                )
+                .send_server_unreliable();
+            }
 
             entity::add_component(local_player_entity, local_yaw(), yaw);
             entity::add_component(local_player_entity, local_pitch(), pitch);
@@ -169,19 +211,19 @@ pub async fn init_players() -> EntityId {
             }
 
             if input_delta.keys.contains(&KeyCode::E) {
-                if let Some(closest_item) = entity::get_component(
+                let radius = entity::get_component(
                     local_player_entity,
-                    crate::components::items::search_result(),
-                ) {
-                    PlayerPickUpItemInput::new(closest_item).send_server_reliable();
+                    crate::components::items::search_radius(),
+                );
+
+                let closest_item =
+                    radius.and_then(|radius| items.nearest(prediction.position, radius, |_| true));
+
+                if let Some(closest_item) = closest_item {
+                    PlayerPickUpItemInput::new(closest_item.id).send_server_reliable();
                 }
             }
 
-            entity::remove_component(
-                local_player_entity,
-                crate::components::items::search_result(),
-            );
-
             if input_delta.keys.contains(&KeyCode::Z) {
                 PlayerDropItemInput::new(false).send_server_reliable();
             }
@@ -224,13 +266,31 @@ pub async fn init_players() -> EntityId {
         let tile_target = crate::map::MAP
             .lock()
             .unwrap()
-            .get(&(intersection.xy() + 0.5).floor().as_ivec2())
+            .get(&position_to_tile(intersection.xy()))
             .copied()
             .unwrap_or(EntityId::null());
 
-        let target = match entity::get_component(tile_target, medium_occupant_ref()) {
-            Some(crop_target) => crop_target,
-            None => tile_target,
+        // fauna are targeted before anything else so that they can be fed,
+        // wherever the ray passes below their height
+        let fauna_length = ((FAUNA_HEIGHT - ray_origin.z) / ray_delta.z).max(0.0);
+        let fauna_start = (ray_origin + ray_delta * fauna_length).xy();
+        let fauna_ray = intersection.xy() - fauna_start;
+        let fauna_target = fauna
+            .raycast(
+                fauna_start,
+                fauna_ray.normalize_or_zero(),
+                fauna_ray.length(),
+                0.0,
+                |_| true,
+            )
+            .map(|hit| hit.id);
+
+        let target = match fauna_target {
+            Some(fauna_target) => fauna_target,
+            None => match entity::get_component(tile_target, medium_occupant_ref()) {
+                Some(crop_target) => crop_target,
+                None => tile_target,
+            },
         };
 
         // de-highlight previous target
@@ -1,32 +1,75 @@
 use ambient_api::{prelude::*, rand};
+use flowerpot_core::{
+    crops::{grow_medium, pick_spread_target, CropClass, CROP_GRID_CELL_SIZE, PEST_CROP_CHOICES},
+    partitioning::GridIndex,
+    pollination::wear_off_pollination,
+    soil::Soil,
+};
 
 use crate::{
     components::{crops::*, *},
+    map::get_tile_neighbor,
     messages,
+    shared::partitioning::Partitioning,
 };
 
 /// Sets up crop-related systems.
 pub fn init_crops() {
+    // crops sit on tiles, so grids index them more cheaply than QBVHs
+    let crop_grid = || GridIndex::new(CROP_GRID_CELL_SIZE);
+
     // init cover crop searching
-    crate::shared::partitioning::init_qbvh(
-        cover_crop_occupant(),
+    Partitioning::with_index(cover_crop_occupant(), crop_grid()).track_nearest(
         search_cover_crop_radius(),
         search_cover_crop_result(),
+        |_, _| true,
+    );
+
+    // init searching for the medium crops that pests want to eat
+    Partitioning::with_index(pest_attractiveness(), crop_grid()).track_k_nearest(
+        search_medium_crop_radius(),
+        PEST_CROP_CHOICES,
+        search_medium_crop_results(),
+        |_, _| true,
     );
 
+    // init searching for flowering crops
+    Partitioning::with_index(nectar(), crop_grid()).track_nearest(
+        search_flower_radius(),
+        search_flower_result(),
+        |_, _| true,
+    );
+
+    // wear off crop pollination
+    query(pollination()).each_frame(|crops| {
+        for (e, remaining) in crops {
+            match wear_off_pollination(remaining, delta_time()) {
+                Some(remaining) => entity::set_component(e, pollination(), remaining),
+                None => entity::remove_component(e, pollination()),
+            }
+        }
+    });
+
     messages::GrowTick::subscribe({
         let growable_query = query((map::tile(), cover_crop_occupant())).build();
         let mut rng = rand::thread_rng();
         move |_, _| {
             for (tile, (_, cover_crop)) in growable_query.evaluate() {
-                crate::map::for_random_neighbors(&mut rng, tile, |neighbor| {
-                    if entity::has_component(neighbor, cover_crop_occupant()) {
-                        None
-                    } else {
-                        entity::add_component(neighbor, cover_crop_occupant(), cover_crop);
-                        Some(())
-                    }
-                });
+                let target = pick_spread_target(
+                    &mut rng,
+                    |dir| get_tile_neighbor(tile, dir),
+                    |neighbor| {
+                        !entity::has_component(*neighbor, cover_crop_occupant())
+                            && get_soil(*neighbor).is_some_and(|soil| soil.can_grow_cover_crop())
+                    },
+                );
+
+                let Some(neighbor) = target else { continue };
+                let Some(mut soil) = get_soil(neighbor) else { continue };
+                if soil.grow_cover_crop() {
+                    entity::set_component(neighbor, map::nutrients(), soil.nutrients);
+                    entity::add_component(neighbor, cover_crop_occupant(), cover_crop);
+                }
             }
         }
     });
@@ -36,19 +79,22 @@ pub fn init_crops() {
         let mut rng = rand::thread_rng();
         move |_, _| {
             for (crop, (tile, _, class)) in growable_query.evaluate() {
-                if let Some(seed_class) = entity::get_component(class, seed_ref()) {
-                    crate::map::for_random_neighbors(&mut rng, tile, |neighbor| {
-                        if entity::has_component(neighbor, medium_occupant_ref()) {
-                            None
-                        } else {
-                            let child = new_medium(seed_class, neighbor);
-                            entity::add_component(neighbor, medium_occupant_ref(), child);
-                            Some(())
-                        }
-                    });
+                let class = get_crop_class(class);
+                let pollinated = entity::has_component(crop, pollination());
+                let growth = grow_medium(
+                    &mut rng,
+                    &class,
+                    pollinated,
+                    |dir| get_tile_neighbor(tile, dir),
+                    |neighbor| !entity::has_component(*neighbor, medium_occupant_ref()),
+                );
+
+                for (neighbor, seed_class) in growth.spread {
+                    let child = new_medium(seed_class, neighbor);
+                    entity::add_component(neighbor, medium_occupant_ref(), child);
                 }
 
-                if let Some(next_class) = entity::get_component(class, next_growth_phase_ref()) {
+                if let Some(next_class) = growth.next_phase {
                     let next_instance = new_medium(next_class, tile);
                     entity::set_component(tile, medium_occupant_ref(), next_instance);
                     entity::despawn(crop);
@@ -65,15 +111,64 @@ pub fn init_crops() {
     });
 }
 
+/// Helper function to get the soil of a tile.
+fn get_soil(tile: EntityId) -> Option<Soil> {
+    let nutrients = entity::get_component(tile, map::nutrients())?;
+    Some(Soil { nutrients })
+}
+
 /// Helper function to instantiate a medium crop.
 pub fn new_medium(class: EntityId, tile: EntityId) -> EntityId {
-    Entity::new()
+    let mut crop = Entity::new()
         .with(class_ref(), class)
         .with(is_medium_crop(), ())
         .with(map::on_tile(), tile)
         .with(
             map::position(),
             entity::get_component(tile, map::position()).unwrap(),
-        )
-        .spawn()
+        );
+
+    // make the crop searchable by pests and pollinators
+    if let Some(attractiveness) = entity::get_component(class, pest_attractiveness()) {
+        crop.set(pest_attractiveness(), attractiveness);
+    }
+
+    if let Some(class_nectar) = entity::get_component(class, nectar()) {
+        crop.set(nectar(), class_nectar);
+    }
+
+    crop.spawn()
+}
+
+/// Regresses a medium crop eaten by a pest to its damaged phase, or destroys
+/// it if it has none.
+pub fn damage_medium(crop: EntityId) {
+    let (Some(class), Some(tile)) = (
+        entity::get_component(crop, class_ref()),
+        entity::get_component(crop, map::on_tile()),
+    ) else {
+        return;
+    };
+
+    match get_crop_class(class).damaged_phase {
+        Some(damaged_class) => {
+            let damaged = new_medium(damaged_class, tile);
+            entity::set_component(tile, medium_occupant_ref(), damaged);
+        }
+        None => entity::remove_component(tile, medium_occupant_ref()),
+    }
+
+    entity::despawn(crop);
+}
+
+/// Helper function to read the properties of a medium crop class.
+pub fn get_crop_class(class: EntityId) -> CropClass<EntityId> {
+    CropClass {
+        seed: entity::get_component(class, seed_ref()),
+        next_growth_phase: entity::get_component(class, next_growth_phase_ref()),
+        damaged_phase: entity::get_component(class, damaged_phase_ref()),
+        pest_attractiveness: entity::get_component(class, pest_attractiveness()).unwrap_or(0.0),
+        sustenance: entity::get_component(class, sustenance()).unwrap_or(0.0),
+        nectar: entity::get_component(class, nectar()).unwrap_or(0.0),
+    }
 }
@@ -1,9 +1,10 @@
-use std::sync::atomic::AtomicBool;
+use std::{collections::HashMap, sync::atomic::AtomicBool};
 
 use ambient_api::{
     components::core::{app::name, rendering::color},
     prelude::*,
 };
+use flowerpot_core::content::{self, CropDef, ItemDef, WorkstationDef};
 use once_cell::sync::OnceCell;
 
 /// A single-instance, lazily-spawned entity for use with the Prototype pattern.
@@ -33,167 +34,157 @@ impl PrototypeEntity {
     }
 }
 
-macro_rules! expand_props {
-    ($e:expr, $component:ident: $value:expr $(, $component_tail:ident: $value_tail:expr)* $(,)?) => {
-        expand_props!(Entity::with($e, $component(), $value.into()) $(, $component_tail: $value_tail)*)
-    };
-    ($e:expr) => ($e);
+lazy_static::lazy_static! {
+    static ref ITEM_CLASSES: HashMap<&'static str, PrototypeEntity> = content::ITEMS
+        .iter()
+        .map(|def| (def.id, PrototypeEntity::new(move |e| add_item_class(e, def))))
+        .collect();
+    static ref CROP_CLASSES: HashMap<&'static str, PrototypeEntity> = content::CROPS
+        .iter()
+        .map(|def| (def.id, PrototypeEntity::new(move |e| add_crop_class(e, def))))
+        .collect();
+    static ref WORKSTATION_CLASSES: HashMap<&'static str, PrototypeEntity> =
+        content::WORKSTATIONS
+            .iter()
+            .map(|def| {
+                let prototype = PrototypeEntity::new(move |e| add_workstation_class(e, def));
+                (def.id, prototype)
+            })
+            .collect();
 }
 
-macro_rules! def_entity {
-    ($($component:ident: $value:expr),* $(,)?) => {
-        expand_props!(Entity::new(), $($component: $value),*)
-    }
+/// Retrieves the class entity of the item with the given content ID.
+pub fn item_class(id: &str) -> EntityId {
+    ITEM_CLASSES
+        .get(id)
+        .unwrap_or_else(|| panic!("Unknown item class {:?}", id))
+        .get()
 }
 
-macro_rules! def_prototype {
-    ($item_name:ident $(, $component:ident: $value:expr)* $(,)?) => {
-        ::lazy_static::lazy_static! {
-            pub static ref $item_name: PrototypeEntity = PrototypeEntity::new(move |e| {
-                entity::add_components(e, def_entity!($($component: $value),*));
-            });
-        }
-    }
+/// Retrieves the class entity of the crop with the given content ID.
+pub fn crop_class(id: &str) -> EntityId {
+    CROP_CLASSES
+        .get(id)
+        .unwrap_or_else(|| panic!("Unknown crop class {:?}", id))
+        .get()
 }
 
-def_prototype!(
-    BLUE_ITEM,
-    name: "Blue Item",
-    color: vec4(0.0, 0.0, 1.0, 1.0),
-);
-
-def_prototype!(
-    GREEN_ITEM,
-    name: "Green Item",
-    color: vec4(0.0, 1.0, 0.0, 1.0),
-);
-
-def_prototype!(
-    YELLOW_ITEM,
-    name: "Yellow Item",
-    color: vec4(1.0, 1.0, 0.0, 1.0),
-);
-
-pub mod items {
-    use super::*;
-
-    pub use crate::components::items::*;
-
-    def_prototype!(
-        MAIZE,
-        name: "Maize",
-        prefab_path: "assets/items/maize.glb",
-    );
-
-    def_prototype!(
-        MAIZE_SEEDS,
-        name: "Maize Seeds",
-        prefab_path: "assets/items/maize_seeds.glb",
-        plantable_crop_class_ref: super::crops::MAIZE_STAGE_1.get(),
-    );
-
-    def_prototype!(
-        TOMATO_SEEDS,
-        name: "Tomato Seeds",
-        prefab_path: "assets/items/tomato_seeds.glb",
-        plantable_crop_class_ref: super::crops::TOMATOES_STAGE_1.get(),
-    );
+/// Retrieves the class entity of the workstation with the given content ID.
+pub fn workstation_class(id: &str) -> EntityId {
+    WORKSTATION_CLASSES
+        .get(id)
+        .unwrap_or_else(|| panic!("Unknown workstation class {:?}", id))
+        .get()
 }
 
-pub mod crops {
-    use super::*;
+fn add_item_class(e: EntityId, def: &ItemDef) {
+    use crate::components::items::*;
+
+    let mut components = Entity::new()
+        .with(name(), def.name.to_string())
+        .with(quality(), def.quality);
+
+    if let Some(path) = def.prefab_path {
+        components.set(prefab_path(), path.to_string());
+    }
+
+    if let Some(rgba) = def.color {
+        components.set(color(), Vec4::from_array(rgba));
+    }
+
+    if let Some(crop) = def.plantable_crop {
+        components.set(plantable_crop_class_ref(), crop_class(crop));
+    }
 
+    if def.places_hive {
+        components.set(places_hive(), ());
+    }
+
+    if def.places_pen {
+        components.set(places_pen(), ());
+    }
+
+    if def.fertilizer > 0.0 {
+        components.set(fertilizer(), def.fertilizer);
+    }
+
+    entity::add_components(e, components);
+}
+
+fn add_crop_class(e: EntityId, def: &CropDef) {
     use crate::components::crops::*;
 
-    def_prototype!(
-        MAIZE,
-        prefab_url: "assets/crops/maize4.glb",
-        seed_ref: MAIZE_STAGE_1.get(),
-        harvest_item_class_ref: items::MAIZE.get(),
-    );
-
-    def_prototype!(
-        MAIZE_STAGE_3,
-        prefab_url: "assets/crops/maize3.glb",
-        next_growth_phase_ref: MAIZE.get(),
-        harvest_item_class_ref: items::MAIZE_SEEDS.get(),
-    );
-
-    def_prototype!(
-        MAIZE_STAGE_2,
-        prefab_url: "assets/crops/maize2.glb",
-        next_growth_phase_ref: MAIZE_STAGE_3.get(),
-        harvest_item_class_ref: items::MAIZE_SEEDS.get(),
-    );
-
-    def_prototype!(
-        MAIZE_STAGE_1,
-        prefab_url: "assets/crops/maize1.glb",
-        next_growth_phase_ref: MAIZE_STAGE_2.get(),
-        harvest_item_class_ref: items::MAIZE_SEEDS.get(),
-    );
-
-    def_prototype!(
-        IRIS,
-        prefab_url: "assets/crops/iris.glb",
-        seed_ref: IRIS.get(),
-    );
-
-    def_prototype!(
-        TOMATOES,
-        prefab_url: "assets/crops/tomatoes5.glb",
-        seed_ref: TOMATOES_STAGE_1.get(),
-        harvest_item_class_ref: items::TOMATO_SEEDS.get(),
-    );
-
-    def_prototype!(
-        TOMATOES_STAGE_4,
-        prefab_url: "assets/crops/tomatoes4.glb",
-        next_growth_phase_ref: TOMATOES.get(),
-        harvest_item_class_ref: items::TOMATO_SEEDS.get(),
-    );
-
-    def_prototype!(
-        TOMATOES_STAGE_3,
-        prefab_url: "assets/crops/tomatoes3.glb",
-        next_growth_phase_ref: TOMATOES_STAGE_4.get(),
-        harvest_item_class_ref: items::TOMATO_SEEDS.get(),
-    );
-
-    def_prototype!(
-        TOMATOES_STAGE_2,
-        prefab_url: "assets/crops/tomatoes2.glb",
-        next_growth_phase_ref: TOMATOES_STAGE_3.get(),
-        harvest_item_class_ref: items::TOMATO_SEEDS.get(),
-    );
-
-    def_prototype!(
-        TOMATOES_STAGE_1,
-        prefab_url: "assets/crops/tomatoes1.glb",
-        next_growth_phase_ref: TOMATOES_STAGE_2.get(),
-        harvest_item_class_ref: items::TOMATO_SEEDS.get(),
-    );
+    let mut components = Entity::new().with(prefab_url(), def.prefab_url.to_string());
+
+    if let Some(next) = def.next_growth_phase {
+        components.set(next_growth_phase_ref(), crop_class(next));
+    }
+
+    if let Some(seed) = def.seed {
+        components.set(seed_ref(), crop_class(seed));
+    }
+
+    if let Some(item) = def.harvest_item {
+        components.set(harvest_item_class_ref(), item_class(item));
+    }
+
+    if let Some(damaged) = def.damaged_phase {
+        components.set(damaged_phase_ref(), crop_class(damaged));
+    }
+
+    if def.pest_attractiveness > 0.0 {
+        components.set(pest_attractiveness(), def.pest_attractiveness);
+        components.set(crate::components::sustenance(), def.sustenance);
+    }
+
+    if def.nectar > 0.0 {
+        components.set(nectar(), def.nectar);
+    }
+
+    entity::add_components(e, components);
+}
+
+fn add_workstation_class(e: EntityId, def: &WorkstationDef) {
+    let components = Entity::new()
+        .with(name(), def.name.to_string())
+        .with(color(), Vec4::from_array(def.color));
+
+    entity::add_components(e, components);
 }
 
 pub fn init_data() {
     use crate::components::crafting::*;
-    use items::*;
-
-    def_entity!(
-        recipe: (),
-        primary_ingredient: BLUE_ITEM.get(),
-        secondary_ingredient: YELLOW_ITEM.get(),
-        primary_yield: GREEN_ITEM.get(),
-        secondary_yield: EntityId::null(),
-    )
-    .spawn();
-
-    def_entity!(
-        recipe: (),
-        primary_ingredient: MAIZE.get(),
-        secondary_ingredient: EntityId::null(),
-        primary_yield: MAIZE_SEEDS.get(),
-        secondary_yield: MAIZE_SEEDS.get(),
-    )
-    .spawn();
+
+    let item_or_null = |id: Option<&str>| id.map_or(EntityId::null(), item_class);
+
+    for def in content::RECIPES {
+        let recipe_entity = Entity::new()
+            .with(recipe(), ())
+            .with(primary_ingredient(), item_class(def.primary_ingredient))
+            .with(
+                secondary_ingredient(),
+                item_or_null(def.secondary_ingredient),
+            )
+            .with(
+                required_workstation_ref(),
+                def.workstation.map_or(EntityId::null(), workstation_class),
+            )
+            .with(duration(), def.duration)
+            .spawn();
+
+        let tables = [(false, def.primary_yield), (true, def.secondary_yield)];
+        for (is_secondary, table) in tables {
+            for outcome in table {
+                Entity::new()
+                    .with(yield_recipe_ref(), recipe_entity)
+                    .with(yield_is_secondary(), is_secondary)
+                    .with(yield_item_ref(), item_or_null(outcome.item))
+                    .with(yield_weight(), outcome.weight)
+                    .with(yield_quality_weight(), outcome.quality_weight)
+                    .with(yield_min_quantity(), outcome.min_quantity)
+                    .with(yield_max_quantity(), outcome.max_quantity)
+                    .spawn();
+            }
+        }
+    }
 }
@@ -1,113 +1,726 @@
-use ambient_api::{components::core::transform::rotation, prelude::*};
+use std::collections::{HashMap, HashSet};
 
-use crate::components::*;
+use ambient_api::{
+    components::core::{
+        prefab::prefab_from_url,
+        transform::{rotation, scale},
+    },
+    concepts::make_transformable,
+    prelude::*,
+    rand,
+};
+use flowerpot_core::{
+    content::{self, SpeciesDef},
+    crops::CropClass,
+    fauna::{
+        Behavior, Diet, FaunaAction, FaunaState, Instincts, Locomotion, Metabolism, Surroundings,
+    },
+    foraging::{self, pick_food, pick_meal, FoodHere, FoodNearby},
+    husbandry::{follow_owner, pen_bounds, Producer, Taming, PEN_ENTRY_DISTANCE, PEN_RADIUS},
+    map::position_to_tile,
+    movement::movement_heading,
+    pollination::{Hive, HiveConfig, POLLINATION_DURATION},
+    population::{nearest_mates, pair_mates, Breeder, LifeCycle, Lifetime},
+    soil::excretion,
+};
+
+use crate::{
+    components::*,
+    map::{MAP_SIZE, POPULATION_CAP},
+    shared::partitioning::Partitioning,
+};
 
 /// Sets up queries relating to fauna.
 pub fn init_fauna() {
-    // decrease fauna fullness by hunger rate
-    query((fullness(), hunger_rate()))
+    // init prey searching
+    crate::shared::partitioning::init_qbvh(prey(), search_prey_radius(), search_prey_result());
+
+    // init threat searching, where tamed fauna trust their owner
+    Partitioning::new(threat()).track_nearest(wariness(), search_threat_result(), |e, threat| {
+        entity::get_component(e, owner_ref()) != Some(threat)
+    });
+
+    // deplete fullness and refill stamina
+    query((stamina(), fullness(), passive_metabolism(), hunger_rate()))
         .requires(fauna())
         .each_frame(|entities| {
-            for (e, (old_fullness, hunger_rate)) in entities {
-                let fullness_delta = hunger_rate * delta_time();
-                let new_fullness = old_fullness - fullness_delta;
-                entity::set_component(e, fullness(), new_fullness);
+            for (e, (stamina, fullness, passive_metabolism, hunger_rate)) in entities {
+                let mut state = FaunaState { stamina, fullness };
+
+                let metabolism = Metabolism {
+                    passive_metabolism,
+                    hunger_rate,
+                };
+
+                state.metabolize(&metabolism, delta_time());
+                write_state(e, &state);
             }
         });
 
-    // kill fauna with non-positive fullness
-    change_query(fullness())
+    // kill starved fauna
+    change_query((stamina(), fullness()))
         .track_change(fullness())
         .requires(fauna())
         .bind(|changed| {
-            for (e, fullness) in changed {
-                if fullness <= 0.0 {
-                    entity::despawn(e);
+            for (e, (stamina, fullness)) in changed {
+                if (FaunaState { stamina, fullness }).is_starved() {
+                    die(e);
                 }
             }
         });
 
-    // passive metabolism refills fauna stamina
-    query((stamina(), passive_metabolism()))
+    // spawn pollinators from hives
+    query((
+        map::position(),
+        hive_spawn_interval(),
+        hive_capacity(),
+        hive_spawn_cooldown(),
+    ))
+    .requires(hive())
+    .each_frame({
+        let pollinators = query(home_hive_ref()).build();
+        move |hives| {
+            let mut populations: HashMap<EntityId, usize> = HashMap::new();
+            for (_e, home) in pollinators.evaluate() {
+                *populations.entry(home).or_default() += 1;
+            }
+
+            for (e, (position, spawn_interval, capacity, spawn_cooldown)) in hives {
+                let config = HiveConfig {
+                    spawn_interval,
+                    capacity: capacity as usize,
+                };
+
+                let mut hive = Hive { spawn_cooldown };
+                let population = populations.get(&e).copied().unwrap_or(0);
+                if hive.tick(&config, delta_time(), population) {
+                    spawn_pollinator(e, position);
+                }
+
+                entity::set_component(e, hive_spawn_cooldown(), hive.spawn_cooldown);
+            }
+        }
+    });
+
+    // age fauna and remove those that die of old age
+    query((age(), breeding_cooldown()))
         .requires(fauna())
         .each_frame(|entities| {
-            for (e, (old_stamina, metabolism)) in entities {
-                let new_stamina = old_stamina + metabolism * delta_time();
-                entity::set_component(e, stamina(), new_stamina);
+            for (e, (age, breeding_cooldown)) in entities {
+                let Some(life_cycle) = get_life_cycle(e) else { continue };
+                let mut lifetime = Lifetime {
+                    age,
+                    breeding_cooldown,
+                };
+
+                lifetime.grow_older(delta_time());
+                if lifetime.is_expired(&life_cycle) {
+                    die(e);
+                } else {
+                    write_lifetime(e, &lifetime);
+                }
             }
         });
 
-    // move fauna
+    // breed fauna that are ready and point the others toward their mates
     query((
-        fauna(),
         map::position(),
-        map::on_tile(),
-        stamina(),
+        species(),
         fullness(),
-        movement_cost(),
-        movement_distance(),
-        search_cover_crop_result(),
+        age(),
+        breeding_cooldown(),
     ))
-    .excludes(movement_step())
+    .requires(fauna())
     .each_frame(|entities| {
-        for (
-            e,
-            (
-                _fauna,
-                map_pos,
-                tile,
-                old_stamina,
-                old_fullness,
-                movement_cost,
-                movement_distance,
-                search_result,
-            ),
-        ) in entities
-        {
-            if old_stamina < movement_cost {
+        let mut populations: HashMap<String, usize> = HashMap::new();
+        let mut breeders = Vec::new();
+        for (e, (position, species, fullness, age, breeding_cooldown)) in entities {
+            *populations.entry(species.clone()).or_default() += 1;
+
+            let Some(life_cycle) = get_life_cycle(e) else { continue };
+            let state = FaunaState {
+                stamina: 0.0,
+                fullness,
+            };
+
+            let lifetime = Lifetime {
+                age,
+                breeding_cooldown,
+            };
+
+            if !lifetime.can_breed(&life_cycle, &state) {
+                entity::remove_component(e, search_mate_result());
                 continue;
             }
 
-            entity::remove_component(e, search_cover_crop_result());
+            breeders.push(Breeder {
+                id: e,
+                species,
+                position,
+                mate_radius: life_cycle.mate_radius,
+                mate_search_radius: life_cycle.mate_search_radius,
+            });
+        }
+
+        let mut paired = HashSet::new();
+        for (a, b) in pair_mates(&breeders, &mut populations, POPULATION_CAP) {
+            paired.insert(a);
+            paired.insert(b);
+            breed(a, b);
+        }
+
+        for breeder in breeders.iter() {
+            if paired.contains(&breeder.id) || populations[&breeder.species] >= POPULATION_CAP {
+                entity::remove_component(breeder.id, search_mate_result());
+            }
+        }
 
-            let new_stamina = old_stamina - movement_cost;
-            entity::set_component(e, stamina(), new_stamina);
+        breeders.retain(|breeder| {
+            !paired.contains(&breeder.id) && populations[&breeder.species] < POPULATION_CAP
+        });
 
-            if let Some(cover_crop) = entity::get_component(tile, cover_crop_occupant()) {
-                if let Some(sustenance) = entity::get_component(cover_crop, sustenance()) {
-                    let new_fullness = old_fullness + sustenance;
-                    entity::set_component(e, fullness(), new_fullness);
-                    entity::remove_component(tile, cover_crop_occupant());
-                    continue;
+        for (breeder, mate) in breeders.iter().zip(nearest_mates(&breeders)) {
+            match mate {
+                Some(mate) => {
+                    entity::add_component(breeder.id, search_mate_result(), breeders[mate].id)
                 }
+                None => entity::remove_component(breeder.id, search_mate_result()),
             }
+        }
+    });
 
-            if search_result.is_null() {
-                continue;
+    // pen livestock that enter a pen, where results only change once
+    // livestock leave the last pen found, so that livestock let out of a pen
+    // aren't penned again right away
+    crate::shared::partitioning::init_qbvh(pen(), search_pen_radius(), search_pen_result());
+
+    change_query(search_pen_result())
+        .track_change(search_pen_result())
+        .requires(owner_ref())
+        .excludes(penned_ref())
+        .bind(|changes| {
+            for (e, pen) in changes {
+                if !pen.is_null() {
+                    entity::add_component(e, penned_ref(), pen);
+                }
             }
+        });
 
-            let target_pos = match entity::get_component(search_result, map::position()) {
-                Some(target_pos) => target_pos,
-                None => continue,
-            };
+    // produce items from livestock
+    query((
+        map::position(),
+        produce_item_class_ref(),
+        produce_interval(),
+        produce_cooldown(),
+    ))
+    .requires(owner_ref())
+    .each_frame(|entities| {
+        for (e, (position, class, interval, cooldown)) in entities {
+            let mut producer = Producer { cooldown };
+            if producer.tick(interval, delta_time()) {
+                Entity::new()
+                    .with(map::position(), position)
+                    .with(items::class_ref(), class)
+                    .spawn();
+            }
 
-            let target_delta = target_pos - map_pos;
-            let movement_delta = target_delta.clamp_length_max(movement_distance);
-            let movement_theta = -movement_delta.angle_between(-Vec2::X);
+            entity::set_component(e, produce_cooldown(), producer.cooldown);
+        }
+    });
 
-            if !movement_theta.is_finite() {
-                continue;
+    // run each idle fauna's behavior state machine
+    query((map::position(), stamina(), fullness(), behavior()))
+        .requires(fauna())
+        .excludes(movement_step())
+        .each_frame({
+            let mut rng = rand::thread_rng();
+            move |entities| {
+                for (e, (map_pos, stamina, fullness, behavior_index)) in entities {
+                    let Some((locomotion, instincts)) = get_species(e) else { continue };
+                    let mut state = FaunaState { stamina, fullness };
+                    let current = Behavior::from_index(behavior_index).unwrap_or(Behavior::Idle);
+                    let tile = entity::get_component(e, map::on_tile());
+                    let (sensed, meal) = sense(e, map_pos, tile, &mut rng);
+
+                    // head along paths to food and mates instead of straight at them
+                    let surroundings = Surroundings {
+                        food: sensed.food.and_then(|food| follow_path(e, food)),
+                        mate: sensed.mate.and_then(|mate| follow_path(e, mate)),
+                        owner: sensed.owner.and_then(|owner| follow_path(e, owner)),
+                        ..sensed
+                    };
+
+                    let (next, action) = state.update(
+                        current,
+                        &locomotion,
+                        &instincts,
+                        map_pos,
+                        &surroundings,
+                        &mut rng,
+                    );
+
+                    if action == FaunaAction::Rest {
+                        continue;
+                    }
+
+                    entity::set_component(e, behavior(), next.index());
+                    write_state(e, &state);
+
+                    let path_target = match next {
+                        Behavior::SeekFood => sensed.food,
+                        Behavior::SeekMate => sensed.mate,
+                        Behavior::Follow => sensed.owner,
+                        _ => None,
+                    };
+
+                    set_path_target(e, path_target);
+
+                    // eaters return some of what they eat to the soil
+                    if let (FaunaAction::Eat, Some(tile), Some(sustenance)) =
+                        (action, tile, sensed.food_here)
+                    {
+                        crate::map::fertilize_tile(tile, excretion(sustenance));
+                    }
+
+                    match action {
+                        FaunaAction::Eat => match meal {
+                            Some(Meal::CoverCrop(tile)) => {
+                                entity::remove_component(tile, cover_crop_occupant());
+                            }
+                            Some(Meal::MediumCrop(crop)) => crate::crop::damage_medium(crop),
+                            Some(Meal::Flower(crop)) => {
+                                entity::add_component(
+                                    crop,
+                                    crops::pollination(),
+                                    POLLINATION_DURATION,
+                                );
+                            }
+                            Some(Meal::Prey(prey)) => entity::despawn(prey),
+                            None => {}
+                        },
+                        FaunaAction::Hop(movement_delta) => start_hop(e, map_pos, movement_delta),
+                        FaunaAction::Rest | FaunaAction::Idle => {}
+                    }
+                }
             }
+        });
+}
+
+/// Helper function to despawn a fauna that has died, returning its remains to
+/// the soil that it died on.
+fn die(e: EntityId) {
+    let tile = entity::get_component(e, map::on_tile());
+    if let (Some(tile), Some(remains)) = (tile, entity::get_component(e, remains())) {
+        crate::map::fertilize_tile(tile, remains);
+    }
+
+    entity::despawn(e);
+}
+
+/// Helper function to read a fauna's per-species parameters.
+fn get_species(e: EntityId) -> Option<(Locomotion, Instincts)> {
+    let locomotion = Locomotion {
+        movement_cost: entity::get_component(e, movement_cost())?,
+        movement_distance: entity::get_component(e, movement_distance())?,
+    };
+
+    let instincts = Instincts {
+        hungry_fullness: entity::get_component(e, hungry_fullness())?,
+        sated_fullness: entity::get_component(e, sated_fullness())?,
+        wander_chance: entity::get_component(e, wander_chance())?,
+    };
+
+    Some((locomotion, instincts))
+}
+
+/// Helper function to determine what a fauna eats.
+fn get_diet(e: EntityId) -> Diet {
+    if entity::has_component(e, search_prey_radius()) {
+        Diet::Carnivore
+    } else if entity::has_component(e, search_flower_radius()) {
+        Diet::Pollinator
+    } else {
+        Diet::Herbivore
+    }
+}
+
+/// Helper function to read a fauna's per-species life cycle parameters.
+fn get_life_cycle(e: EntityId) -> Option<LifeCycle> {
+    Some(LifeCycle {
+        maturity_age: entity::get_component(e, maturity_age())?,
+        max_age: entity::get_component(e, max_age())?,
+        breeding_fullness: entity::get_component(e, breeding_fullness())?,
+        breeding_cost: entity::get_component(e, breeding_cost())?,
+        breeding_interval: entity::get_component(e, breeding_interval())?,
+        mate_radius: entity::get_component(e, mate_radius())?,
+        mate_search_radius: entity::get_component(e, mate_search_radius())?,
+    })
+}
+
+/// Helper function to write a fauna's life cycle state back to its components.
+fn write_lifetime(e: EntityId, lifetime: &Lifetime) {
+    entity::set_component(e, age(), lifetime.age);
+    entity::set_component(e, breeding_cooldown(), lifetime.breeding_cooldown);
+}
+
+/// Helper function to charge two parents for breeding and spawn their
+/// offspring between them.
+fn breed(a: EntityId, b: EntityId) {
+    let Some(life_cycle) = get_life_cycle(a) else { return };
+
+    let mut offspring_fullness = 0.0;
+    let mut positions = Vec::with_capacity(2);
+    for parent in [a, b] {
+        let (Some(position), Some(fullness), Some(age), Some(breeding_cooldown)) = (
+            entity::get_component(parent, map::position()),
+            entity::get_component(parent, fullness()),
+            entity::get_component(parent, age()),
+            entity::get_component(parent, breeding_cooldown()),
+        ) else {
+            return;
+        };
 
-            let components = Entity::new()
-                .with(rotation(), Quat::from_rotation_z(movement_theta))
-                .with(movement_step(), 0.0)
-                .with(movement_duration(), 0.25)
-                .with(movement_start(), map_pos)
-                .with(movement_target(), map_pos + movement_delta)
-                .with(movement_height(), 0.5);
+        let mut state = FaunaState {
+            stamina: entity::get_component(parent, stamina()).unwrap_or(0.0),
+            fullness,
+        };
 
-            entity::add_components(e, components);
+        let mut lifetime = Lifetime {
+            age,
+            breeding_cooldown,
+        };
+
+        offspring_fullness += lifetime.breed(&life_cycle, &mut state);
+        positions.push(position);
+        write_state(parent, &state);
+        write_lifetime(parent, &lifetime);
+    }
+
+    let Some(def) = entity::get_component(a, species()).and_then(|id| content::species(&id)) else {
+        return;
+    };
+
+    // offspring inherit the average of their parents' movement costs
+    let (min_cost, max_cost) = def.movement_cost;
+    let parent_cost = |e| entity::get_component(e, movement_cost()).unwrap_or(min_cost);
+    let offspring_cost = ((parent_cost(a) + parent_cost(b)) / 2.0).clamp(min_cost, max_cost);
+
+    species_traits(def)
+        .with(map::position(), positions[0].lerp(positions[1], 0.5))
+        .with(behavior(), Behavior::Idle.index())
+        .with(stamina(), 0.0)
+        .with(fullness(), offspring_fullness)
+        .with(movement_cost(), offspring_cost)
+        .with(age(), 0.0)
+        .with(breeding_cooldown(), 0.0)
+        .spawn();
+}
+
+/// What a fauna would eat if it ate right now.
+type Meal = foraging::Meal<EntityId, EntityId>;
+
+/// Helper function to gather what a fauna knows about its surroundings, along
+/// with what it would eat on its current tile.
+fn sense(
+    e: EntityId,
+    map_pos: Vec2,
+    tile: Option<EntityId>,
+    rng: &mut impl Rng,
+) -> (Surroundings, Option<Meal>) {
+    let diet = get_diet(e);
+    let is_pest = entity::has_component(e, search_medium_crop_radius());
+
+    let here = FoodHere {
+        cover_crop: tile.and_then(|tile| {
+            let cover_crop = entity::get_component(tile, cover_crop_occupant())?;
+            Some((tile, entity::get_component(cover_crop, sustenance())?))
+        }),
+        medium_crop: tile
+            .and_then(|tile| entity::get_component(tile, crops::medium_occupant_ref()))
+            .and_then(|crop| Some((crop, get_crop_class(crop)?))),
+        prey: find_prey_here(e, tile)
+            .and_then(|prey| Some((prey, entity::get_component(prey, sustenance())?))),
+    };
+
+    let nearby = FoodNearby {
+        medium_crops: entity::get_component(e, search_medium_crop_results())
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|crop| Some((crop, get_crop_class(crop)?)))
+            .collect(),
+        cover_crop: entity::get_component(e, search_cover_crop_result()),
+        flower: entity::get_component(e, search_flower_result()),
+        prey: entity::get_component(e, search_prey_result()),
+    };
+
+    let meal = pick_meal(diet, is_pest, here, rng);
+    let food_result = pick_food(diet, nearby, rng);
+    let food_here = meal.map(|(_, sustenance)| sustenance);
+    let meal = meal.map(|(meal, _)| meal);
+
+    let food = food_result.and_then(|result| entity::get_component(result, map::position()));
+
+    let threat = entity::get_component(e, search_threat_result())
+        .and_then(|result| entity::get_component(result, map::position()));
+
+    let mate = entity::get_component(e, search_mate_result())
+        .and_then(|result| entity::get_component(result, map::position()));
+
+    let map_bounds = (Vec2::ZERO, (MAP_SIZE - 1).as_vec2());
+    let pen = entity::get_component(e, penned_ref())
+        .and_then(|pen| entity::get_component(pen, map::position()));
+
+    let (owner, bounds) = match pen {
+        Some(pen) => {
+            let (min, max) = pen_bounds(pen);
+            (None, (min.max(map_bounds.0), max.min(map_bounds.1)))
         }
-    });
+        None => {
+            let owner = entity::get_component(e, owner_ref())
+                .and_then(|owner| entity::get_component(owner, map::position()))
+                .and_then(|owner| follow_owner(map_pos, owner));
+
+            (owner, map_bounds)
+        }
+    };
+
+    let surroundings = Surroundings {
+        food_here,
+        food,
+        threat,
+        mate,
+        owner,
+        bounds,
+    };
+
+    (surroundings, meal)
+}
+
+/// Helper function to get where a fauna should head next on its way to a
+/// target, or `None` if its path there is blocked.
+///
+/// Fauna without path rules, like flying pollinators, head straight there.
+fn follow_path(e: EntityId, target: Vec2) -> Option<Vec2> {
+    let following = entity::get_component(e, map::path_target())
+        .is_some_and(|path_target| position_to_tile(path_target) == position_to_tile(target));
+
+    if !following || !entity::has_component(e, map::medium_crop_path_cost()) {
+        return Some(target);
+    }
+
+    entity::get_component(e, map::path_next())
+}
+
+/// Helper function to start or stop a fauna following a path.
+fn set_path_target(e: EntityId, target: Option<Vec2>) {
+    match target {
+        Some(target) if entity::has_component(e, map::medium_crop_path_cost()) => {
+            entity::add_component(e, map::path_target(), target);
+        }
+        _ => entity::remove_component(e, map::path_target()),
+    }
+}
+
+/// Helper function to read the class of a medium crop.
+fn get_crop_class(crop: EntityId) -> Option<CropClass<EntityId>> {
+    let class = entity::get_component(crop, crops::class_ref())?;
+    Some(crate::crop::get_crop_class(class))
+}
+
+/// Helper function to find a carnivore's nearest prey if it's on the same tile.
+fn find_prey_here(e: EntityId, tile: Option<EntityId>) -> Option<EntityId> {
+    let prey = entity::get_component(e, search_prey_result())?;
+    let prey_tile = entity::get_component(prey, map::on_tile())?;
+    (tile == Some(prey_tile)).then_some(prey)
+}
+
+/// Feeds a fauna an item class on behalf of a player and returns whether the
+/// fauna ate it.
+///
+/// Wild fauna build up trust until they're tamed by the player that feeds
+/// them. Owners feed penned livestock to let them out of their pen.
+pub fn feed(e: EntityId, player: EntityId, class: EntityId) -> bool {
+    if entity::get_component(e, taming_food_class_ref()) != Some(class) {
+        return false;
+    }
+
+    if let Some(owner) = entity::get_component(e, owner_ref()) {
+        if owner != player || !entity::has_component(e, penned_ref()) {
+            return false;
+        }
+
+        entity::remove_component(e, penned_ref());
+        return true;
+    }
+
+    let Some(needed) = entity::get_component(e, tame_trust()) else { return false };
+    let mut taming = Taming {
+        trust: entity::get_component(e, trust()).unwrap_or(0.0),
+    };
+
+    if taming.feed(needed) {
+        tame(e, player);
+    }
+
+    entity::add_component(e, trust(), taming.trust);
+    true
+}
+
+/// Helper function to make a player the owner of a fauna.
+fn tame(e: EntityId, player: EntityId) {
+    entity::add_component(e, owner_ref(), player);
+    entity::add_component(e, search_pen_radius(), PEN_ENTRY_DISTANCE);
+
+    if let Some(interval) = entity::get_component(e, produce_interval()) {
+        entity::add_component(e, produce_cooldown(), interval);
+    }
+}
+
+/// Places a livestock pen at a position.
+pub fn spawn_pen(position: Vec2) -> EntityId {
+    Entity::new()
+        .with(map::position(), position)
+        .with(map::footprint_extents(), Vec2::splat(PEN_RADIUS))
+        .with_default(pen())
+        .spawn()
+}
+
+/// Places a pollinator hive at a position.
+pub fn spawn_hive(position: Vec2) -> EntityId {
+    Entity::new()
+        .with(map::position(), position)
+        .with_default(map::obstacle())
+        .with_default(hive())
+        .with(hive_spawn_interval(), 10.0)
+        .with(hive_spawn_cooldown(), 0.0)
+        .with(hive_capacity(), 5)
+        .spawn()
+}
+
+/// Helper function to spawn a pollinator from its home hive. Hives stay empty
+/// if no species is a pollinator.
+fn spawn_pollinator(hive: EntityId, position: Vec2) {
+    let Some(def) = content::SPECIES
+        .iter()
+        .find(|def| def.diet == Diet::Pollinator)
+    else {
+        return;
+    };
+
+    let pollinator = spawn_fauna(def, position, &mut rand::thread_rng());
+    entity::add_component(pollinator, home_hive_ref(), hive);
+}
+
+/// Spawns a newly-matured adult of a species at a position.
+pub fn spawn_fauna(def: &SpeciesDef, position: Vec2, rng: &mut impl Rng) -> EntityId {
+    let (min_cost, max_cost) = def.movement_cost;
+    species_traits(def)
+        .with(map::position(), position)
+        .with(behavior(), Behavior::Idle.index())
+        .with(stamina(), 0.0)
+        .with(fullness(), def.fullness)
+        .with(movement_cost(), rng.gen_range(min_cost..=max_cost))
+        .with(age(), def.life_cycle.maturity_age)
+        .with(breeding_cooldown(), 0.0)
+        .spawn()
+}
+
+/// Helper function to build the traits that every individual of a species
+/// shares, without any of their state.
+fn species_traits(def: &SpeciesDef) -> Entity {
+    let mut animal = Entity::new()
+        .with_merge(make_transformable())
+        .with_default(fauna())
+        .with(species(), def.id.to_string())
+        .with(map::footprint_radius(), def.footprint)
+        .with(passive_metabolism(), def.metabolism.passive_metabolism)
+        .with(hunger_rate(), def.metabolism.hunger_rate)
+        .with(hungry_fullness(), def.instincts.hungry_fullness)
+        .with(sated_fullness(), def.instincts.sated_fullness)
+        .with(wander_chance(), def.instincts.wander_chance)
+        .with(movement_distance(), def.movement_distance)
+        .with(maturity_age(), def.life_cycle.maturity_age)
+        .with(max_age(), def.life_cycle.max_age)
+        .with(breeding_fullness(), def.life_cycle.breeding_fullness)
+        .with(breeding_cost(), def.life_cycle.breeding_cost)
+        .with(breeding_interval(), def.life_cycle.breeding_interval)
+        .with(mate_radius(), def.life_cycle.mate_radius)
+        .with(mate_search_radius(), def.life_cycle.mate_search_radius);
+
+    if let Some(url) = def.prefab_url {
+        animal.set(prefab_from_url(), asset::url(url).unwrap());
+    }
+
+    if def.scale != 1.0 {
+        animal.set(scale(), Vec3::splat(def.scale));
+    }
+
+    if def.is_prey {
+        animal.set(prey(), ());
+    }
+
+    if def.is_threat {
+        animal.set(threat(), ());
+    }
+
+    let search_radius = match def.diet {
+        Diet::Herbivore => search_cover_crop_radius(),
+        Diet::Carnivore => search_prey_radius(),
+        Diet::Pollinator => search_flower_radius(),
+    };
+
+    animal.set(search_radius, def.search_radius);
+
+    if def.crop_search_radius > 0.0 {
+        animal.set(search_medium_crop_radius(), def.crop_search_radius);
+    }
+
+    if def.wariness > 0.0 {
+        animal.set(wariness(), def.wariness);
+    }
+
+    if let Some(rules) = def.path_rules {
+        animal.set(map::medium_crop_path_cost(), rules.medium_crop_cost);
+    }
+
+    if def.sustenance > 0.0 {
+        animal.set(sustenance(), def.sustenance);
+    }
+
+    if def.remains > 0.0 {
+        animal.set(remains(), def.remains);
+    }
+
+    if let Some(food) = def.taming_food {
+        animal.set(taming_food_class_ref(), crate::data::item_class(food));
+        animal.set(tame_trust(), def.tame_trust);
+    }
+
+    if let Some(item) = def.produce {
+        animal.set(produce_item_class_ref(), crate::data::item_class(item));
+        animal.set(produce_interval(), def.produce_interval);
+    }
+
+    animal
+}
+
+/// Helper function to write a fauna's state back to its components.
+fn write_state(e: EntityId, state: &FaunaState) {
+    entity::set_component(e, stamina(), state.stamina);
+    entity::set_component(e, fullness(), state.fullness);
+}
+
+/// Helper function to start a fauna hopping by a delta from its position.
+fn start_hop(e: EntityId, map_pos: Vec2, movement_delta: Vec2) {
+    let Some(movement_theta) = movement_heading(movement_delta) else { return };
+
+    let components = Entity::new()
+        .with(rotation(), Quat::from_rotation_z(movement_theta))
+        .with(movement_step(), 0.0)
+        .with(movement_duration(), 0.25)
+        .with(movement_start(), map_pos)
+        .with(movement_target(), map_pos + movement_delta)
+        .with(movement_height(), 0.5);
+
+    entity::add_components(e, components);
 }
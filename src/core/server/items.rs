@@ -1,9 +1,10 @@
-use std::{
-    collections::HashMap,
-    sync::{Arc, Mutex},
-};
+use std::sync::{Arc, Mutex};
 
-use ambient_api::prelude::*;
+use ambient_api::{
+    prelude::*,
+    rand::{self, rngs::StdRng, SeedableRng},
+};
+use flowerpot_core::crafting::{distribute_yields, Recipe, RecipeStore, YieldOutcome};
 
 use crate::components::{
     crafting::*,
@@ -12,115 +13,172 @@ use crate::components::{
     map,
 };
 use crate::player::PlayerEntities;
+use crate::shared::partitioning::Partitioning;
 
-/// Wasm-side crafting recipe data.
-pub struct CraftingRecipe {
-    pub recipe_entity: EntityId,
-    pub primary_ingredient: EntityId,
-    pub secondary_ingredient: EntityId,
-    pub primary_yield: EntityId,
-    pub secondary_yield: EntityId,
-}
+/// The furthest that players can reach to pick up items. This is a little
+/// further than clients search for items in, to allow for latency.
+pub const PICKUP_RADIUS: f32 = 1.5;
 
-/// The set of all available crafting recipes.
-pub struct RecipeStore {
-    recipes: HashMap<(EntityId, EntityId), CraftingRecipe>,
-}
-
-impl RecipeStore {
-    pub fn new() -> Self {
-        Self {
-            recipes: Default::default(),
-        }
-    }
+/// The seed for crafting yields. Set this to the `crafting::rng_seed` resource
+/// of an earlier playtest to reproduce its crafting outcomes, or leave it as
+/// `None` to pick a random seed.
+pub const CRAFTING_SEED: Option<u64> = None;
 
-    pub fn match_ingredients(
-        &self,
-        left_ingredient: EntityId,
-        right_ingredient: EntityId,
-    ) -> Option<(&CraftingRecipe, bool)> {
-        if let Some(recipe) = self.recipes.get(&(left_ingredient, right_ingredient)) {
-            Some((recipe, false))
-        } else if let Some(recipe) = self.recipes.get(&(right_ingredient, left_ingredient)) {
-            Some((recipe, true))
-        } else {
-            None
-        }
-    }
-
-    pub fn apply_craft(
-        &self,
-        left_held: EntityId,
-        right_held: EntityId,
-    ) -> Option<(EntityId, EntityId)> {
-        let (recipe, right_is_primary) = self.match_ingredients(left_held, right_held)?;
-        if !right_is_primary {
-            Some((recipe.primary_yield, recipe.secondary_yield))
-        } else {
-            Some((recipe.secondary_yield, recipe.primary_yield))
-        }
-    }
+/// Helper function to convert a possibly-null entity reference to an option.
+fn non_null(e: EntityId) -> Option<EntityId> {
+    Some(e).filter(|e| !e.is_null())
 }
 
 pub fn init_server_items() {
-    let store = RecipeStore::new();
+    let store: RecipeStore<EntityId> = RecipeStore::new();
     let store = Arc::new(Mutex::new(store));
 
+    // seed crafting yields so that playtest outcomes can be reproduced
+    let seed = CRAFTING_SEED.unwrap_or_else(rand::random);
+    entity::add_component(entity::resources(), rng_seed(), seed);
+    let rng = Arc::new(Mutex::new(StdRng::seed_from_u64(seed)));
+
     spawn_query((
         recipe(),
         primary_ingredient(),
         secondary_ingredient(),
-        primary_yield(),
-        secondary_yield(),
+        required_workstation_ref(),
+        duration(),
     ))
     .bind({
         let store = store.clone();
         move |recipes| {
             let mut store = store.lock().unwrap();
-            for (
-                e,
-                (_recipe, primary_ingredient, secondary_ingredient, primary_yield, secondary_yield),
-            ) in recipes
+            for (e, (_recipe, primary_ingredient, secondary_ingredient, workstation, duration)) in
+                recipes
             {
-                let recipe = CraftingRecipe {
-                    recipe_entity: e,
+                let recipe = Recipe {
+                    id: e,
                     primary_ingredient,
-                    secondary_ingredient,
-                    primary_yield,
-                    secondary_yield,
+                    secondary_ingredient: non_null(secondary_ingredient),
+                    workstation: non_null(workstation),
+                    duration,
                 };
 
-                let recipe_key = (primary_ingredient, secondary_ingredient);
-
-                if store.recipes.contains_key(&recipe_key)
-                    || store
-                        .recipes
-                        .contains_key(&(secondary_ingredient, primary_ingredient))
-                {
+                if !store.insert(recipe) {
                     eprintln!("Duplicate crafting recipe");
-                    continue;
                 }
+            }
+        }
+    });
+
+    spawn_query((
+        yield_recipe_ref(),
+        yield_is_secondary(),
+        yield_item_ref(),
+        yield_weight(),
+        yield_quality_weight(),
+        yield_min_quantity(),
+        yield_max_quantity(),
+    ))
+    .bind({
+        let store = store.clone();
+        move |outcomes| {
+            let mut store = store.lock().unwrap();
+            for (
+                _e,
+                (recipe, is_secondary, item, weight, quality_weight, min_quantity, max_quantity),
+            ) in outcomes
+            {
+                let outcome = YieldOutcome {
+                    item: non_null(item),
+                    weight,
+                    quality_weight,
+                    min_quantity,
+                    max_quantity,
+                };
 
-                store.recipes.insert(recipe_key, recipe);
+                store.add_yield(recipe, is_secondary, outcome);
             }
         }
     });
 
+    // index workstations and items on the map for crafting and pickups
+    let workstations = Partitioning::new(workstation_class_ref());
+    let items = Partitioning::new(class_ref());
+
     crate::messages::PlayerCraftInput::subscribe({
         let store = store.clone();
+        let rng = rng.clone();
         move |source, _| {
             let Some(mut player) = PlayerEntities::from_source(&source) else { return; };
+
+            if entity::has_component(player.entity, active_recipe_ref()) {
+                return;
+            }
+
             let store = store.lock().unwrap();
-            let crafted = store.apply_craft(player.left_held, player.right_held);
-            if let Some((new_left_held, new_right_held)) = crafted {
-                player.set_left_held(new_left_held);
-                player.set_right_held(new_right_held);
+            let matched =
+                store.match_ingredients(non_null(player.left_held), non_null(player.right_held));
+            let Some((recipe, right_is_primary)) = matched else { return };
+
+            let workstation = match recipe.workstation {
+                None => EntityId::null(),
+                Some(class) => match find_workstation(&workstations, player.entity, class) {
+                    Some(workstation) => workstation,
+                    None => return,
+                },
+            };
+
+            if recipe.duration > 0.0 {
+                entity::add_components(
+                    player.entity,
+                    Entity::new()
+                        .with(progress(), 0.0)
+                        .with(active_recipe_ref(), recipe.id)
+                        .with(active_workstation_ref(), workstation),
+                );
+            } else {
+                let mut rng = rng.lock().unwrap();
+                complete_craft(&store, &mut rng, &mut player, recipe, right_is_primary);
+            }
+        }
+    });
+
+    // advance timed crafts, cancelling them if their conditions no longer hold
+    query((progress(), active_recipe_ref(), active_workstation_ref())).each_frame({
+        let store = store.clone();
+        let rng = rng.clone();
+        move |players| {
+            let store = store.lock().unwrap();
+            let mut rng = rng.lock().unwrap();
+            for (e, (old_progress, recipe_entity, workstation)) in players {
+                let Some(mut player) = PlayerEntities::from_entity(e) else { continue };
+
+                let matched = store
+                    .match_ingredients(non_null(player.left_held), non_null(player.right_held))
+                    .filter(|(recipe, _)| recipe.id == recipe_entity);
+
+                let Some((recipe, right_is_primary)) = matched else {
+                    cancel_craft(e);
+                    continue;
+                };
+
+                if !workstation.is_null() && !is_in_reach(e, workstation) {
+                    cancel_craft(e);
+                    continue;
+                }
+
+                let new_progress = old_progress + delta_time();
+                if new_progress < recipe.duration {
+                    entity::set_component(e, progress(), new_progress);
+                    continue;
+                }
+
+                cancel_craft(e);
+                complete_craft(&store, &mut rng, &mut player, recipe, right_is_primary);
             }
         }
     });
 
     crate::messages::PlayerSwapItemsInput::subscribe(move |source, _| {
         let Some(mut player) = PlayerEntities::from_source(&source) else { return; };
+        cancel_craft(player.entity);
         let left_held = player.left_held;
         let right_held = player.right_held;
         player.set_left_held(right_held);
@@ -129,10 +187,12 @@ pub fn init_server_items() {
 
     crate::messages::PlayerPickUpItemInput::subscribe(move |source, data| {
         let Some(mut player) = PlayerEntities::from_source(&source) else { return };
+        let Some(position) = entity::get_component(player.entity, map::position()) else { return };
 
-        match entity::get_component(data.target, map::position()) {
-            Some(_) => {}   // TODO range checking for pickups
-            None => return, // invalid item grab
+        // only pick up items that are on the map and within reach
+        let in_reach = items.within_radius(position, PICKUP_RADIUS, |item| item == data.target);
+        if in_reach.is_empty() {
+            return;
         }
 
         let Some(class) = entity::get_component(data.target, class_ref()) else {
@@ -150,6 +210,7 @@ pub fn init_server_items() {
 
         // TODO proper item instance management
         entity::remove_component(data.target, map::position());
+        items.remove(data.target);
     });
 
     crate::messages::PlayerDropItemInput::subscribe(move |source, data| {
@@ -188,9 +249,57 @@ pub fn init_server_items() {
             player.left_held
         };
 
-        if let Some(crop) = entity::get_component(held, plantable_crop_class_ref()) {
-            if !entity::has_component(data.target_ref, map::tile())
-                || entity::has_component(data.target_ref, medium_occupant_ref())
+        if entity::has_component(data.target_ref, crate::components::fauna()) {
+            if held.is_null() || !crate::fauna::feed(data.target_ref, player.entity, held) {
+                return;
+            }
+
+            if data.hand {
+                player.set_right_held(EntityId::null());
+            } else {
+                player.set_left_held(EntityId::null());
+            }
+        } else if entity::has_component(held, places_hive())
+            || entity::has_component(held, places_pen())
+        {
+            if crate::shared::map::blocks_players(Some(data.target_ref))
+                || !entity::has_component(data.target_ref, map::tile())
+            {
+                return;
+            }
+
+            let tile = data.target_ref;
+            let Some(position) = entity::get_component(tile, map::position()) else { return };
+            if entity::has_component(held, places_hive()) {
+                crate::fauna::spawn_hive(position);
+            } else {
+                crate::fauna::spawn_pen(position);
+            }
+
+            if data.hand {
+                player.set_right_held(EntityId::null());
+            } else {
+                player.set_left_held(EntityId::null());
+            }
+        } else if let Some(amount) = entity::get_component(held, fertilizer()) {
+            // fertilize the tile under a targeted crop too
+            let tile = entity::get_component(data.target_ref, map::on_tile())
+                .unwrap_or(data.target_ref);
+
+            if !entity::has_component(tile, map::tile()) {
+                return;
+            }
+
+            crate::map::fertilize_tile(tile, amount);
+
+            if data.hand {
+                player.set_right_held(EntityId::null());
+            } else {
+                player.set_left_held(EntityId::null());
+            }
+        } else if let Some(crop) = entity::get_component(held, plantable_crop_class_ref()) {
+            if crate::shared::map::blocks_players(Some(data.target_ref))
+                || !entity::has_component(data.target_ref, map::tile())
             {
                 return;
             }
@@ -236,3 +345,65 @@ pub fn init_server_items() {
         }
     });
 }
+
+/// Helper function to replace a player's ingredients with a recipe's rolled
+/// yields. Items that don't fit into the player's hands spill onto the ground.
+fn complete_craft(
+    store: &RecipeStore<EntityId>,
+    rng: &mut StdRng,
+    player: &mut PlayerEntities,
+    recipe: &Recipe<EntityId>,
+    right_is_primary: bool,
+) {
+    let item_quality = |class| entity::get_component(class, quality()).unwrap_or(0.0);
+    let total_quality = item_quality(player.left_held) + item_quality(player.right_held);
+    let (primary, secondary) = store.roll_yields(recipe, total_quality, rng);
+
+    let result = distribute_yields(primary, secondary, right_is_primary);
+    player.set_left_held(result.left_held.unwrap_or(EntityId::null()));
+    player.set_right_held(result.right_held.unwrap_or(EntityId::null()));
+
+    let Some(position) = entity::get_component(player.entity, map::position()) else { return };
+    for class in result.spilled {
+        let offset = vec2(rng.gen_range(-0.3..0.3), rng.gen_range(-0.3..0.3));
+        Entity::new()
+            .with(map::position(), position + offset)
+            .with(class_ref(), class)
+            .spawn();
+    }
+}
+
+/// Helper function to find the nearest workstation of the given class within
+/// a player's search radius.
+fn find_workstation(
+    workstations: &Partitioning,
+    player: EntityId,
+    class: EntityId,
+) -> Option<EntityId> {
+    let radius = entity::get_component(player, workstation_search_radius())?;
+    let position = entity::get_component(player, map::position())?;
+    workstations
+        .nearest(position, radius, |workstation| {
+            entity::get_component(workstation, workstation_class_ref()) == Some(class)
+        })
+        .map(|hit| hit.id)
+}
+
+/// Helper function to test if a workstation is within a player's search radius.
+fn is_in_reach(player: EntityId, workstation: EntityId) -> bool {
+    let in_reach = || {
+        let radius = entity::get_component(player, workstation_search_radius())?;
+        let player_pos = entity::get_component(player, map::position())?;
+        let workstation_pos = entity::get_component(workstation, map::position())?;
+        Some(player_pos.distance(workstation_pos) <= radius)
+    };
+
+    in_reach().unwrap_or(false)
+}
+
+/// Helper function to stop a player's timed craft, if any.
+fn cancel_craft(player: EntityId) {
+    entity::remove_component(player, progress());
+    entity::remove_component(player, active_recipe_ref());
+    entity::remove_component(player, active_workstation_ref());
+}
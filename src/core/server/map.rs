@@ -1,78 +1,93 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
 
 use ambient_api::{
-    components::core::{prefab::prefab_from_url, rendering::pbr_material_from_url},
-    concepts::make_transformable,
-    glam::IVec2,
-    prelude::*,
-    rand,
+    components::core::rendering::pbr_material_from_url, glam::IVec2, prelude::*, rand,
+};
+use flowerpot_core::{
+    map::{position_to_tile, scatter_wild_crops, OrdinalDirection, TileMap},
+    movement::{movement_elevation, movement_progress},
+    soil::{Soil, INITIAL_NUTRIENTS},
 };
 
 use crate::components::*;
 
-pub enum OrdinalDirection {
-    West,
-    North,
-    East,
-    South,
-}
+/// The size of the map in tiles.
+pub const MAP_SIZE: IVec2 = IVec2::new(32, 32);
 
-impl OrdinalDirection {
-    pub fn closest_to_vec2(v: Vec2) -> Self {
-        use OrdinalDirection::*;
-        if v.x.abs() > v.y.abs() {
-            if v.x > 0.0 {
-                East
-            } else {
-                West
-            }
-        } else {
-            if v.y > 0.0 {
-                South
-            } else {
-                North
-            }
-        }
-    }
+/// The maximum population of each fauna species on the map.
+pub const POPULATION_CAP: usize = 40;
 
-    pub fn as_neighbor_component(&self) -> Component<EntityId> {
-        use OrdinalDirection::*;
-        match self {
-            West => map::west_neighbor_ref(),
-            North => map::north_neighbor_ref(),
-            East => map::east_neighbor_ref(),
-            South => map::south_neighbor_ref(),
-        }
+/// Helper function to get the component referencing a tile's neighbor.
+pub fn neighbor_component(dir: OrdinalDirection) -> Component<EntityId> {
+    use OrdinalDirection::*;
+    match dir {
+        West => map::west_neighbor_ref(),
+        North => map::north_neighbor_ref(),
+        East => map::east_neighbor_ref(),
+        South => map::south_neighbor_ref(),
     }
+}
 
-    pub fn get_tile_neighbor(&self, tile: EntityId) -> Option<EntityId> {
-        let neighbor = self.as_neighbor_component();
-        entity::get_component(tile, neighbor)
-    }
+/// Helper function to look up the neighbor of a tile in a direction.
+pub fn get_tile_neighbor(tile: EntityId, dir: OrdinalDirection) -> Option<EntityId> {
+    entity::get_component(tile, neighbor_component(dir))
 }
 
-pub(crate) fn for_random_neighbors<T>(
-    rng: &mut impl Rng,
-    tile: EntityId,
-    mut cb: impl FnMut(EntityId) -> Option<T>,
-) -> Option<T> {
-    use OrdinalDirection::*;
-    let mut directions = [West, North, East, South];
-    directions.shuffle(rng);
+/// Adds nutrients to a tile's soil.
+pub fn fertilize_tile(tile: EntityId, amount: f32) {
+    let Some(nutrients) = entity::get_component(tile, map::nutrients()) else { return };
+    let mut soil = Soil { nutrients };
+    soil.fertilize(amount);
+    entity::set_component(tile, map::nutrients(), soil.nutrients);
+}
+
+/// The tiles that obstacles are on, so that tiles stay blocked until every
+/// obstacle on them is gone.
+#[derive(Default)]
+struct Obstacles {
+    tiles: HashMap<EntityId, IVec2>,
+    counts: HashMap<IVec2, usize>,
+}
+
+impl Obstacles {
+    /// Moves an obstacle onto a tile, or removes it with `None`, updating
+    /// which tiles are blocked.
+    fn update(&mut self, map: &TileMap<EntityId>, obstacle: EntityId, xy: Option<IVec2>) {
+        let old_xy = self.tiles.get(&obstacle).copied();
+        if old_xy == xy {
+            return;
+        }
 
-    for dir in directions {
-        if let Some(neighbor) = dir.get_tile_neighbor(tile) {
-            if let Some(t) = cb(neighbor) {
-                return Some(t);
+        if let Some(old_xy) = old_xy {
+            self.tiles.remove(&obstacle);
+            let count = self.counts.entry(old_xy).or_default();
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                self.counts.remove(&old_xy);
+                if let Some(tile) = map.get(old_xy) {
+                    entity::remove_component(*tile, map::blocked());
+                }
             }
         }
-    }
 
-    None
+        if let Some(xy) = xy {
+            self.tiles.insert(obstacle, xy);
+            let count = self.counts.entry(xy).or_default();
+            *count += 1;
+            if *count == 1 {
+                if let Some(tile) = map.get(xy) {
+                    entity::add_component(*tile, map::blocked(), ());
+                }
+            }
+        }
+    }
 }
 
-/// Sets up map-related queries and spawns the map.
-pub fn init_map() {
+/// Sets up map-related queries and spawns the map. Returns the map's tiles.
+pub fn init_map() -> Arc<TileMap<EntityId>> {
     // create a grass cover crop prototype
     let grass = Entity::new()
         .with_default(cover_crop())
@@ -84,56 +99,36 @@ pub fn init_map() {
         .spawn();
 
     // spawn some initial tiles and store their IDs
-    let mut map = HashMap::new();
-    let map_width = 32;
-    let map_height = 32;
-    for x in 0..map_width {
-        for y in 0..map_height {
-            let xy = IVec2::new(x, y);
-
-            let tile = Entity::new()
-                .with_default(map::tile())
-                .with_default(map::soil())
-                .with(cover_crop_occupant(), grass)
-                .with(map::position(), xy.as_vec2())
-                .spawn();
-
-            map.insert(xy, tile);
-        }
-    }
+    let map = TileMap::new(MAP_SIZE, |xy| {
+        Entity::new()
+            .with_default(map::tile())
+            .with_default(map::soil())
+            .with(map::nutrients(), INITIAL_NUTRIENTS)
+            .with(cover_crop_occupant(), grass)
+            .with(map::position(), xy.as_vec2())
+            .spawn()
+    });
 
     let map = Arc::new(map);
 
     // connect each tile's neighbor
     for (xy, e) in map.iter() {
-        let xy = *xy;
-        let e = *e;
-
-        if let Some(neighbor) = map.get(&(xy - IVec2::X)) {
-            entity::add_component(e, map::west_neighbor_ref(), *neighbor);
-        }
-
-        if let Some(neighbor) = map.get(&(xy - IVec2::Y)) {
-            entity::add_component(e, map::north_neighbor_ref(), *neighbor);
-        }
-
-        if let Some(neighbor) = map.get(&(xy + IVec2::X)) {
-            entity::add_component(e, map::east_neighbor_ref(), *neighbor);
-        }
-
-        if let Some(neighbor) = map.get(&(xy + IVec2::Y)) {
-            entity::add_component(e, map::south_neighbor_ref(), *neighbor);
+        for dir in OrdinalDirection::ALL {
+            if let Some(neighbor) = map.neighbor(xy, dir).and_then(|xy| map.get(xy)) {
+                entity::add_component(*e, neighbor_component(dir), *neighbor);
+            }
         }
     }
 
+    // path entities across the connected tiles
+    crate::pathing::init_pathing(map.clone());
+
     // set elevation of entities with movement height
     change_query((movement_step(), movement_duration(), movement_height()))
         .track_change(movement_step())
         .bind(|changes| {
             for (e, (step, duration, height)) in changes {
-                let delta = step / duration;
-                let height_factor = delta * (1.0 - delta) * 4.0;
-                let elevation = height * height_factor;
+                let elevation = movement_elevation(height, movement_progress(step, duration));
                 entity::add_component(e, map::elevation(), elevation);
             }
         });
@@ -145,8 +140,7 @@ pub fn init_map() {
             let map = map.clone();
             move |changes| {
                 for (e, xy) in changes {
-                    let xy = (xy + 0.5).floor().as_ivec2();
-                    match map.get(&xy) {
+                    match map.get(position_to_tile(xy)) {
                         None => entity::remove_component(e, map::on_tile()),
                         Some(tile) => entity::add_component(e, map::on_tile(), *tile),
                     }
@@ -154,71 +148,89 @@ pub fn init_map() {
             }
         });
 
-    // spawn some items on the ground
-    {
-        use crate::data::*;
+    // block the tiles that obstacles are on, following obstacles as they move
+    let obstacles = Arc::new(Mutex::new(Obstacles::default()));
+    spawn_query((map::position(), map::obstacle())).bind({
+        let map = map.clone();
+        let obstacles = obstacles.clone();
+        move |entities| {
+            let mut obstacles = obstacles.lock().unwrap();
+            for (e, (xy, _)) in entities {
+                obstacles.update(&map, e, Some(position_to_tile(xy)));
+            }
+        }
+    });
 
-        Entity::new()
-            .with(map::position(), vec2(10.0, 15.0))
-            .with(items::class_ref(), BLUE_ITEM.get())
-            .spawn();
+    change_query((map::position(), map::obstacle()))
+        .track_change(map::position())
+        .bind({
+            let map = map.clone();
+            let obstacles = obstacles.clone();
+            move |entities| {
+                let mut obstacles = obstacles.lock().unwrap();
+                for (e, (xy, _)) in entities {
+                    obstacles.update(&map, e, Some(position_to_tile(xy)));
+                }
+            }
+        });
 
-        Entity::new()
-            .with(map::position(), vec2(17.0, 13.0))
-            .with(items::class_ref(), YELLOW_ITEM.get())
-            .spawn();
+    // unblock tiles once the last obstacle on them is gone
+    despawn_query((map::position(), map::obstacle())).bind({
+        let map = map.clone();
+        move |entities| {
+            let mut obstacles = obstacles.lock().unwrap();
+            for (e, _) in entities {
+                obstacles.update(&map, e, None);
+            }
+        }
+    });
 
-        Entity::new()
-            .with(map::position(), vec2(10.0, 13.0))
-            .with(items::class_ref(), items::MAIZE_SEEDS.get())
-            .spawn();
+    // spawn the world's initial items, workstations, and crops
+    {
+        use crate::data;
+        use flowerpot_core::content;
 
-        Entity::new()
-            .with(map::position(), vec2(23.0, 17.0))
-            .with(items::class_ref(), items::TOMATO_SEEDS.get())
-            .spawn();
+        for def in content::WORLD_ITEMS {
+            Entity::new()
+                .with(map::position(), Vec2::from_array(def.position))
+                .with(items::class_ref(), data::item_class(def.item))
+                .spawn();
+        }
+
+        for def in content::WORLD_WORKSTATIONS {
+            Entity::new()
+                .with(map::position(), Vec2::from_array(def.position))
+                .with(
+                    crafting::workstation_class_ref(),
+                    data::workstation_class(def.workstation),
+                )
+                .with_default(map::obstacle())
+                .spawn();
+        }
 
-        let maize_tile = *map.get(&IVec2::new(0, 0)).unwrap();
+        for def in content::WORLD_CROPS {
+            let tile = *map.get(IVec2::from_array(def.tile)).unwrap();
 
-        entity::add_component(
-            maize_tile,
-            crate::components::crops::medium_occupant_ref(),
-            crate::crop::new_medium(crops::MAIZE_STAGE_1.get(), maize_tile),
-        );
-    }
+            entity::add_component(
+                tile,
+                crate::components::crops::medium_occupant_ref(),
+                crate::crop::new_medium(data::crop_class(def.crop), tile),
+            );
+        }
 
-    // TODO make bunnies fun to code and important to gameplay
-    return;
-
-    // spawn some bunnies
-    let mut rng = rand::thread_rng();
-    for tile in map
-        .values()
-        .collect::<Vec<_>>()
-        .partial_shuffle(&mut rng, 5)
-        .0
-        .to_vec()
-    {
-        Entity::new()
-            .with_merge(make_transformable())
-            .with(
-                prefab_from_url(),
-                asset::url("assets/fauna/rabbit.glb").unwrap(),
-            )
-            .with_default(fauna())
-            .with_default(bunny())
-            .with(stamina(), 0.0)
-            .with(passive_metabolism(), 1.0)
-            .with(movement_cost(), rng.gen_range(0.4..0.6))
-            .with(movement_distance(), 0.5)
-            .with(search_cover_crop_radius(), 10.0)
-            .with(
-                map::position(),
-                entity::get_component(*tile, map::position()).unwrap(),
-            )
-            .with(fullness(), 1.0)
-            .with(hunger_rate(), 0.1)
-            .with(sustenance(), 10.0)
-            .spawn();
+        let mut rng = rand::thread_rng();
+        let taken = content::placed_tiles();
+        let wild = scatter_wild_crops(content::WILD_CROPS, MAP_SIZE, &taken, &mut rng);
+        for (xy, crop) in wild {
+            let Some(tile) = map.get(xy).copied() else { continue };
+
+            entity::add_component(
+                tile,
+                crate::components::crops::medium_occupant_ref(),
+                crate::crop::new_medium(data::crop_class(crop), tile),
+            );
+        }
     }
+
+    map
 }
@@ -3,7 +3,9 @@ mod data;
 mod fauna;
 mod items;
 mod map;
+mod pathing;
 mod player;
+mod spawning;
 
 #[path = "../shared/mod.rs"]
 mod shared;
@@ -20,6 +22,7 @@ fn init_server() {
     };
 
     use components::{map::position, *};
+    use flowerpot_core::movement::{movement_position, movement_progress};
 
     make_transformable()
         .with_default(sun())
@@ -44,8 +47,8 @@ fn init_server() {
                 entity::remove_component(e, movement_step());
                 entity::add_component(e, position(), target);
             } else {
-                let delta = new_step / duration;
-                let new_pos = start * (1.0 - delta) + target * delta;
+                let progress = movement_progress(new_step, duration);
+                let new_pos = movement_position(start, target, progress);
                 entity::set_component(e, movement_step(), new_step);
                 entity::add_component(e, position(), new_pos);
             }
@@ -61,6 +64,7 @@ pub fn main() {
     data::init_data();
     fauna::init_fauna();
     items::init_server_items();
-    map::init_map();
-    player::init_players();
+    let map = map::init_map();
+    player::init_players(map);
+    spawning::init_spawners();
 }
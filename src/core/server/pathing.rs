@@ -0,0 +1,94 @@
+use std::sync::{Arc, Mutex};
+
+use ambient_api::prelude::*;
+use flowerpot_core::{
+    map::{position_to_tile, TileMap},
+    pathfinding::{find_path, next_waypoint, tile_distance, PathCache, PathRules},
+};
+
+use crate::{
+    components::{crops::medium_occupant_ref, map::*},
+    map::get_tile_neighbor,
+};
+
+/// The number of paths to cache before the cache is emptied.
+pub const PATH_CACHE_CAPACITY: usize = 4096;
+
+/// Sets up queries that path entities with a path target to it.
+pub fn init_pathing(map: Arc<TileMap<EntityId>>) {
+    let cache = Arc::new(Mutex::new(PathCache::new(PATH_CACHE_CAPACITY)));
+
+    // forget cached paths whenever the costs of tiles change
+    change_query(medium_occupant_ref())
+        .track_change(medium_occupant_ref())
+        .bind({
+            let cache = cache.clone();
+            move |_| cache.lock().unwrap().clear()
+        });
+
+    despawn_query(medium_occupant_ref()).bind({
+        let cache = cache.clone();
+        move |_| cache.lock().unwrap().clear()
+    });
+
+    // find the next tile along each entity's path
+    change_query((on_tile(), path_target()))
+        .track_change((on_tile(), path_target()))
+        .bind(move |entities| {
+            let mut cache = cache.lock().unwrap();
+            for (e, (start, target)) in entities {
+                let goal_xy = position_to_tile(target);
+                let Some(goal) = map.get(goal_xy).copied() else {
+                    entity::remove_component(e, path_next());
+                    continue;
+                };
+
+                if start == goal {
+                    entity::add_component(e, path_next(), target);
+                    continue;
+                }
+
+                let rules = get_path_rules(e);
+                let path = cache.get_or_find(rules.cache_key(), start, goal, || {
+                    find_path(
+                        start,
+                        goal,
+                        get_tile_neighbor,
+                        |tile| rules.step_cost(entity::has_component(tile, medium_occupant_ref())),
+                        |tile| {
+                            entity::get_component(tile, position())
+                                .map(|xy| tile_distance(xy, goal_xy.as_vec2()))
+                                .unwrap_or(0.0)
+                        },
+                    )
+                });
+
+                let next = path
+                    .and_then(next_waypoint)
+                    .and_then(|tile| entity::get_component(tile, position()));
+
+                match next {
+                    Some(next) => entity::add_component(e, path_next(), next),
+                    None => entity::remove_component(e, path_next()),
+                }
+            }
+        });
+
+    // stop pathing entities that lose their target
+    despawn_query(path_target()).bind(|entities| {
+        for (e, _) in entities {
+            entity::remove_component(e, path_next());
+        }
+    });
+}
+
+/// Helper function to get the rules that an entity paths by.
+fn get_path_rules(e: EntityId) -> PathRules {
+    let mut rules = PathRules::default();
+
+    if let Some(cost) = entity::get_component(e, medium_crop_path_cost()) {
+        rules.medium_crop_cost = cost;
+    }
+
+    rules
+}
@@ -1,4 +1,4 @@
-use std::f32::consts::{FRAC_PI_2, TAU};
+use std::sync::Arc;
 
 use ambient_api::{
     components::core::player::{player, user_id},
@@ -6,12 +6,18 @@ use ambient_api::{
     prelude::*,
 };
 
+use flowerpot_core::{
+    map::TileMap,
+    player::{refill_budget, unpack_inputs, DEFAULT_MOVEMENT_SPEED},
+};
+
 use crate::{
-    components::{map, player::*},
+    components::{crafting, map, player::*},
     messages,
+    shared::map::blocks_players,
 };
 
-pub fn init_players() {
+pub fn init_players(map: Arc<TileMap<EntityId>>) {
     spawn_query((player(), user_id())).bind(move |players| {
         for (player_entity, (_, user)) in players {
             let make_hand = || {
@@ -28,9 +34,14 @@ pub fn init_players() {
                 player_entity,
                 Entity::new()
                     .with_default(loaded())
+                    .with_default(crate::components::threat())
                     .with(left_hand_ref(), left_hand)
                     .with(right_hand_ref(), right_hand)
                     .with(map::position(), vec2(16.0, 16.0))
+                    .with(input_sequence(), 0)
+                    .with(movement_speed(), DEFAULT_MOVEMENT_SPEED)
+                    .with(movement_budget(), 0.0)
+                    .with(crafting::workstation_search_radius(), 2.0)
                     .with(yaw(), 0.0)
                     .with(pitch(), 0.0),
             );
@@ -44,29 +55,52 @@ pub fn init_players() {
         }
     });
 
+    // give players time to move in every frame
+    query((player(), movement_budget())).each_frame(move |players| {
+        for (e, (_, budget)) in players {
+            entity::set_component(e, movement_budget(), refill_budget(budget, delta_time()));
+        }
+    });
+
+    // apply each movement input once as clients predict, skipping the resent
+    // inputs that were already applied, and cutting inputs short once players
+    // run out of time to move
     messages::PlayerMovementInput::subscribe(move |source, msg| {
         let Some(id) = source.client_entity_id() else { return; };
+        let Some(position) = entity::get_component(id, map::position()) else { return; };
 
-        let direction = msg.direction.normalize_or_zero();
-        let new_yaw = msg.yaw % TAU;
-        let new_pitch = msg.pitch.clamp(-FRAC_PI_2, FRAC_PI_2);
+        let last_sequence = entity::get_component(id, input_sequence()).unwrap_or(0);
+        if msg.sequence <= last_sequence || !entity::has_component(id, loaded()) {
+            return;
+        }
 
+        let speed = entity::get_component(id, movement_speed()).unwrap_or(DEFAULT_MOVEMENT_SPEED);
+        let mut budget = entity::get_component(id, movement_budget()).unwrap_or(0.0);
+        let mut new_position = position;
+        let mut last_input = None;
+        for mut input in unpack_inputs(msg.sequence, &msg.directions, &msg.dts, &msg.looks) {
+            if input.sequence <= last_sequence {
+                continue;
+            }
+
+            budget = input.limit(budget);
+            new_position = input.apply(new_position, speed, |xy| {
+                blocks_players(map.get(xy).copied())
+            });
+            last_input = Some(input);
+        }
+
+        let Some(input) = last_input else { return };
         entity::add_components(
             id,
             Entity::new()
-                .with(movement_direction(), direction)
-                .with(yaw(), new_yaw)
-                .with(pitch(), new_pitch),
+                .with(map::position(), new_position)
+                .with(input_sequence(), input.sequence)
+                .with(movement_budget(), budget)
+                .with(yaw(), input.yaw)
+                .with(pitch(), input.pitch),
         );
     });
-
-    query((player(), loaded(), movement_direction(), yaw())).each_frame(move |players| {
-        for (e, (_, _, direction, yaw)) in players {
-            let speed = 0.1;
-            let direction = Mat2::from_angle(yaw) * direction;
-            entity::mutate_component(e, map::position(), |pos| *pos += direction * speed);
-        }
-    });
 }
 
 /// Helper function to retrieve the entities that compose a player.
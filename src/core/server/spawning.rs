@@ -0,0 +1,81 @@
+use std::collections::HashSet;
+
+use ambient_api::{prelude::*, rand};
+use flowerpot_core::{
+    content::{self, SPAWNERS},
+    spawning::{time_of_day, Spawner},
+};
+
+use crate::{
+    components::{crops::medium_occupant_ref, *},
+    map::POPULATION_CAP,
+};
+
+/// The time in seconds between spawner updates.
+pub const SPAWN_TICK: f32 = 1.0;
+
+/// Sets up the spawners that populate the world with fauna and respawn
+/// species that go locally extinct.
+pub fn init_spawners() {
+    let tile_query = query(map::position()).requires(map::tile()).build();
+    let fauna_query = query((species(), map::on_tile())).requires(fauna()).build();
+
+    run_async(async move {
+        let mut spawners = vec![Spawner::default(); SPAWNERS.len()];
+        let mut clock = 0.0;
+        loop {
+            let tiles = tile_query.evaluate();
+            let fauna = fauna_query.evaluate();
+            tick_spawners(&mut spawners, time_of_day(clock), &tiles, &fauna);
+
+            sleep(SPAWN_TICK).await;
+            clock += SPAWN_TICK;
+        }
+    });
+}
+
+/// Helper function to advance every spawner by one spawn tick.
+fn tick_spawners(
+    spawners: &mut [Spawner],
+    time_of_day: f32,
+    tiles: &[(EntityId, Vec2)],
+    fauna: &[(EntityId, (String, EntityId))],
+) {
+    let mut rng = rand::thread_rng();
+    for (def, spawner) in SPAWNERS.iter().zip(spawners.iter_mut()) {
+        let biome: Vec<_> = tiles
+            .iter()
+            .filter(|(tile, _)| {
+                def.biome.contains(
+                    entity::has_component(*tile, cover_crop_occupant()),
+                    entity::has_component(*tile, medium_occupant_ref()),
+                )
+            })
+            .collect();
+
+        let biome_tiles: HashSet<_> = biome.iter().map(|(tile, _)| *tile).collect();
+
+        let members = fauna
+            .iter()
+            .filter(|(_, (species, _))| species == def.species);
+
+        let population = members.clone().count();
+        let local_population = members
+            .filter(|(_, (_, tile))| biome_tiles.contains(tile))
+            .count();
+
+        let count = spawner.tick(
+            def,
+            SPAWN_TICK,
+            time_of_day,
+            biome.len(),
+            local_population,
+            POPULATION_CAP.saturating_sub(population),
+        );
+
+        let Some(species) = content::species(def.species) else { continue };
+        for (_, position) in biome.choose_multiple(&mut rng, count) {
+            crate::fauna::spawn_fauna(species, *position, &mut rng);
+        }
+    }
+}
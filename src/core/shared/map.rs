@@ -0,0 +1,15 @@
+use ambient_api::prelude::*;
+
+use crate::components::{crops::medium_occupant_ref, map};
+
+/// Whether players can't walk onto a tile, either because it's missing from
+/// the map or because a medium crop or an obstacle is on it.
+pub fn blocks_players(tile: Option<EntityId>) -> bool {
+    match tile {
+        Some(tile) => {
+            entity::has_component(tile, medium_occupant_ref())
+                || entity::has_component(tile, map::blocked())
+        }
+        None => true,
+    }
+}
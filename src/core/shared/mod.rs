@@ -1 +1,2 @@
+pub mod map;
 pub mod partitioning;
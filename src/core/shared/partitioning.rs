@@ -1,128 +1,343 @@
-use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock, RwLockReadGuard};
 
 use ambient_api::ecs::SupportedValue;
 use ambient_api::prelude::*;
-use parry2d::{bounding_volume::Aabb, math::Point, partitioning::*};
-use slab::Slab;
+use flowerpot_core::partitioning::{Footprint, Hit, QbvhIndex, SpatialIndex};
 
 use crate::components::map;
 
-struct PartitioningData {
-    qbvh: Qbvh<usize>,
-    leaves_to_entities: Slab<(EntityId, Aabb)>,
-    entities_to_leaves: HashMap<EntityId, usize>,
-    workspace: QbvhUpdateWorkspace,
+/// A spatial index of the map positions of every entity with a searchable
+/// component, kept up to date as those entities move, spawn, and despawn.
+///
+/// Entities with a footprint component are indexed by the area that it covers
+/// instead of by their position alone.
+///
+/// Indices use a QBVH unless they're given another backend. Changes are
+/// batched until the next query, so the index is flushed at most once per
+/// frame, and only in frames where something changed.
+pub struct Partitioning<I = QbvhIndex<EntityId>> {
+    index: Arc<RwLock<I>>,
 }
 
-impl PartitioningData {
-    fn rebalance(&mut self) {
-        self.qbvh.refit(0.01, &mut self.workspace, |leaf| {
-            self.leaves_to_entities
-                .get(*leaf)
-                .map(|e| e.1.to_owned())
-                .unwrap_or(Aabb::new_invalid())
-        });
+impl<I> Clone for Partitioning<I> {
+    fn clone(&self) -> Self {
+        Self {
+            index: self.index.clone(),
+        }
+    }
+}
 
-        self.qbvh.rebalance(0.01, &mut self.workspace);
+impl Partitioning {
+    /// Starts indexing every entity with a searchable component in a QBVH.
+    pub fn new<SearchableData: SupportedValue + 'static>(
+        searchable_component: Component<SearchableData>,
+    ) -> Self {
+        Self::with_index(searchable_component, QbvhIndex::new())
     }
 }
 
-pub fn init_qbvh<SearchableData: SupportedValue + 'static>(
-    searchable_component: Component<SearchableData>,
-    search_radius_component: Component<f32>,
-    result_component: Component<EntityId>,
-) {
-    let data = PartitioningData {
-        qbvh: Qbvh::new(),
-        leaves_to_entities: Slab::new(),
-        entities_to_leaves: HashMap::new(),
-        workspace: QbvhUpdateWorkspace::default(),
-    };
-
-    let data = Arc::new(RwLock::new(data));
-
-    change_query((map::position(), searchable_component))
-        .track_change((map::position(), searchable_component))
+impl<I: SpatialIndex<EntityId> + 'static> Partitioning<I> {
+    /// Starts indexing every entity with a searchable component in the given
+    /// backend.
+    pub fn with_index<SearchableData: SupportedValue + 'static>(
+        searchable_component: Component<SearchableData>,
+        index: I,
+    ) -> Self {
+        let index = Arc::new(RwLock::new(index));
+
+        change_query((map::position(), searchable_component))
+            .track_change((map::position(), searchable_component))
+            .bind({
+                let index = index.clone();
+                move |entities| {
+                    let mut index = index.write().unwrap();
+                    for (e, (pos, _searchable)) in entities {
+                        index.insert_with_footprint(e, pos, footprint(e));
+                    }
+                }
+            });
+
+        change_query((
+            map::position(),
+            searchable_component,
+            map::footprint_radius(),
+        ))
+        .track_change(map::footprint_radius())
         .bind({
-            let data = data.clone();
+            let index = index.clone();
             move |entities| {
-                let mut data = data.write().unwrap();
-                for (e, (pos, _searchable)) in entities {
-                    let pos_pt = Point::new(pos.x, pos.y);
-                    let aabb = Aabb::new(pos_pt, pos_pt);
-
-                    let leaf = if let Some(leaf) = data.entities_to_leaves.get(&e).copied() {
-                        data.leaves_to_entities.get_mut(leaf).unwrap().1 = aabb;
-                        leaf
-                    } else {
-                        let leaf = data.leaves_to_entities.insert((e, aabb));
-                        data.entities_to_leaves.insert(e, leaf);
-                        leaf
-                    };
-
-                    data.qbvh.pre_update_or_insert(leaf);
+                let mut index = index.write().unwrap();
+                for (e, (pos, _searchable, _radius)) in entities {
+                    index.insert_with_footprint(e, pos, footprint(e));
                 }
+            }
+        });
 
-                data.rebalance();
+        change_query((
+            map::position(),
+            searchable_component,
+            map::footprint_extents(),
+        ))
+        .track_change(map::footprint_extents())
+        .bind({
+            let index = index.clone();
+            move |entities| {
+                let mut index = index.write().unwrap();
+                for (e, (pos, _searchable, _extents)) in entities {
+                    index.insert_with_footprint(e, pos, footprint(e));
+                }
             }
         });
 
-    despawn_query((map::position(), searchable_component)).bind({
-        let data = data.clone();
-        move |entities| {
-            let mut data = data.write().unwrap();
-            for (e, (_pos, _searchable)) in entities {
-                if let Some(leaf) = data.entities_to_leaves.remove(&e) {
-                    data.qbvh.remove(leaf);
-                    data.leaves_to_entities.remove(leaf);
+        despawn_query((map::position(), searchable_component)).bind({
+            let index = index.clone();
+            move |entities| {
+                let mut index = index.write().unwrap();
+                for (e, (_pos, _searchable)) in entities {
+                    index.remove(e);
                 }
             }
+        });
 
-            data.rebalance();
-        }
-    });
+        Self { index }
+    }
 
-    query((map::position(), search_radius_component))
-        .excludes(result_component)
-        .each_frame({
-            let data = data.clone();
-            move |entities| {
-                let data = data.read().unwrap();
-                let mut query_results = Vec::new();
-                for (e, (search_pos, search_radius)) in entities {
-                    let mins = search_pos - search_radius;
-                    let maxs = search_pos + search_radius;
-                    let mins = Point::new(mins.x, mins.y);
-                    let maxs = Point::new(maxs.x, maxs.y);
-                    let search_aabb = Aabb::new(mins, maxs);
-                    data.qbvh.intersect_aabb(&search_aabb, &mut query_results);
-
-                    let mut closest_result = EntityId::null();
-                    let mut closest_distance = search_radius;
-                    for result_leaf in query_results.iter().copied() {
-                        let result = match data.leaves_to_entities.get(result_leaf) {
-                            Some((result, _aabb)) => *result,
-                            None => continue,
-                        };
-
-                        // TODO cache this in leaves_to_entities?
-                        let result_pos = match entity::get_component(result, map::position()) {
-                            None => continue,
-                            Some(pos) => pos,
-                        };
-
-                        let distance = result_pos.distance(search_pos);
-                        if distance < closest_distance {
-                            closest_result = result;
-                            closest_distance = distance;
-                        }
+    /// Removes an entity from the index right away, for entities that stop
+    /// being searchable without despawning.
+    pub fn remove(&self, e: EntityId) {
+        self.index.write().unwrap().remove(e);
+    }
+
+    /// Finds up to `k` indexed entities that pass a filter within a radius of
+    /// a map position, nearest first.
+    pub fn nearest_k(
+        &self,
+        position: Vec2,
+        radius: f32,
+        k: usize,
+        mut filter: impl FnMut(EntityId) -> bool,
+    ) -> Vec<Hit<EntityId>> {
+        self.read()
+            .nearest_k(position, radius, k, |e| on_map(e) && filter(e))
+    }
+
+    /// Finds the nearest indexed entity that passes a filter within a radius
+    /// of a map position.
+    pub fn nearest(
+        &self,
+        position: Vec2,
+        radius: f32,
+        mut filter: impl FnMut(EntityId) -> bool,
+    ) -> Option<Hit<EntityId>> {
+        self.read()
+            .nearest(position, radius, |e| on_map(e) && filter(e))
+    }
+
+    /// Finds every indexed entity that passes a filter within a radius of a
+    /// map position, nearest first.
+    pub fn within_radius(
+        &self,
+        position: Vec2,
+        radius: f32,
+        mut filter: impl FnMut(EntityId) -> bool,
+    ) -> Vec<Hit<EntityId>> {
+        self.read()
+            .within_radius(position, radius, |e| on_map(e) && filter(e))
+    }
+
+    /// Finds every indexed entity that passes a filter inside of a box on the
+    /// map, given by its minimum and maximum corners.
+    pub fn within_aabb(
+        &self,
+        min: Vec2,
+        max: Vec2,
+        mut filter: impl FnMut(EntityId) -> bool,
+    ) -> Vec<EntityId> {
+        self.read()
+            .within_aabb(min, max, |e| on_map(e) && filter(e))
+    }
+
+    /// Finds the first indexed entity that passes a filter along a ray on the
+    /// map, with the ray swept by the given radius.
+    pub fn raycast(
+        &self,
+        origin: Vec2,
+        direction: Vec2,
+        max_distance: f32,
+        radius: f32,
+        mut filter: impl FnMut(EntityId) -> bool,
+    ) -> Option<Hit<EntityId>> {
+        self.read()
+            .raycast(origin, direction, max_distance, radius, |e| {
+                on_map(e) && filter(e)
+            })
+    }
+
+    /// Helper function to read the index, first flushing it if it has changed
+    /// since the last query.
+    fn read(&self) -> RwLockReadGuard<'_, I> {
+        self.index.write().unwrap().flush();
+        self.index.read().unwrap()
+    }
+
+    /// Keeps the nearest indexed entity within each searcher's radius in its
+    /// result component, or a null entity if there are none.
+    ///
+    /// The filter takes the searcher and then the candidate, and searchers
+    /// never find themselves. Results are only written when they change, so
+    /// change queries on the result component see each new result once.
+    pub fn track_nearest(
+        &self,
+        search_radius_component: Component<f32>,
+        result_component: Component<EntityId>,
+        filter: impl Fn(EntityId, EntityId) -> bool + 'static,
+    ) {
+        self.track(
+            search_radius_component,
+            result_component,
+            move |index, e, search_pos, search_radius| {
+                index
+                    .nearest(search_pos, search_radius, |result| {
+                        result != e && on_map(result) && filter(e, result)
+                    })
+                    .map(|hit| hit.id)
+                    .unwrap_or(EntityId::null())
+            },
+        );
+    }
+
+    /// Keeps up to `k` of the nearest indexed entities within each searcher's
+    /// radius in its results component, nearest first.
+    ///
+    /// Like [Partitioning::track_nearest], the filter takes the searcher and
+    /// then the candidate, searchers never find themselves, and results are
+    /// only written when they change.
+    pub fn track_k_nearest(
+        &self,
+        search_radius_component: Component<f32>,
+        k: usize,
+        results_component: Component<Vec<EntityId>>,
+        filter: impl Fn(EntityId, EntityId) -> bool + 'static,
+    ) {
+        self.track(
+            search_radius_component,
+            results_component,
+            move |index, e, search_pos, search_radius| {
+                index
+                    .nearest_k(search_pos, search_radius, k, |result| {
+                        result != e && on_map(result) && filter(e, result)
+                    })
+                    .into_iter()
+                    .map(|hit| hit.id)
+                    .collect()
+            },
+        );
+    }
+
+    /// Calls `on_enter` with a searcher and then an indexed entity whenever
+    /// that entity comes within the searcher's radius, and `on_exit` whenever
+    /// it leaves.
+    ///
+    /// Entities leave when they're despawned or removed from the index, and
+    /// everything leaves a searcher that stops searching.
+    pub fn track_within_radius(
+        &self,
+        search_radius_component: Component<f32>,
+        mut on_enter: impl FnMut(EntityId, EntityId) + 'static,
+        mut on_exit: impl FnMut(EntityId, EntityId) + 'static,
+    ) {
+        let partitioning = self.clone();
+        let mut inside: HashMap<EntityId, HashSet<EntityId>> = HashMap::new();
+        query((map::position(), search_radius_component)).each_frame(move |entities| {
+            // search before calling back, so that callbacks can query the index
+            let found: HashMap<EntityId, HashSet<EntityId>> = {
+                let index = partitioning.read();
+                entities
+                    .into_iter()
+                    .map(|(e, (search_pos, search_radius))| {
+                        let hits = index.within_radius(search_pos, search_radius, |result| {
+                            result != e && on_map(result)
+                        });
+
+                        (e, hits.into_iter().map(|hit| hit.id).collect())
+                    })
+                    .collect()
+            };
+
+            inside.retain(|e, old| {
+                let new = found.get(e);
+                for &result in old.iter() {
+                    if !new.is_some_and(|new| new.contains(&result)) {
+                        on_exit(*e, result);
                     }
+                }
 
-                    entity::add_component(e, result_component, closest_result);
+                new.is_some()
+            });
 
-                    query_results.clear();
+            for (e, new) in found {
+                let old = inside.entry(e).or_default();
+                for &result in new.difference(old) {
+                    on_enter(e, result);
                 }
+
+                *old = new;
             }
         });
+    }
+
+    /// Helper function to search for each searcher every frame, and to write
+    /// the results that have changed since the last search.
+    fn track<ResultData: SupportedValue + PartialEq + 'static>(
+        &self,
+        search_radius_component: Component<f32>,
+        result_component: Component<ResultData>,
+        search: impl Fn(&I, EntityId, Vec2, f32) -> ResultData + 'static,
+    ) {
+        let partitioning = self.clone();
+        query((map::position(), search_radius_component)).each_frame(move |entities| {
+            let index = partitioning.read();
+            for (e, (search_pos, search_radius)) in entities {
+                let result = search(&index, e, search_pos, search_radius);
+                if entity::get_component(e, result_component).as_ref() != Some(&result) {
+                    entity::add_component(e, result_component, result);
+                }
+            }
+        });
+    }
+}
+
+/// Helper function to skip indexed entities that are no longer on the map, in
+/// case the index hasn't caught up with them yet.
+fn on_map(e: EntityId) -> bool {
+    entity::has_component(e, map::position())
+}
+
+/// Helper function to get the footprint of an entity, preferring a box over a
+/// circle if it has both.
+fn footprint(e: EntityId) -> Footprint {
+    if let Some(extents) = entity::get_component(e, map::footprint_extents()) {
+        return Footprint::Box(extents);
+    }
+
+    match entity::get_component(e, map::footprint_radius()) {
+        Some(radius) => Footprint::Circle(radius),
+        None => Footprint::Point,
+    }
+}
+
+/// Keeps the nearest entity with a searchable component in the result
+/// component of every entity searching within a radius.
+///
+/// Returns the index of the searchable entities, so that it can also be
+/// queried directly.
+pub fn init_qbvh<SearchableData: SupportedValue + 'static>(
+    searchable_component: Component<SearchableData>,
+    search_radius_component: Component<f32>,
+    result_component: Component<EntityId>,
+) -> Partitioning {
+    let partitioning = Partitioning::new(searchable_component);
+    partitioning.track_nearest(search_radius_component, result_component, |_, _| true);
+    partitioning
 }